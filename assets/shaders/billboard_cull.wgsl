@@ -0,0 +1,75 @@
+#import bevy_render::view::View
+
+struct MultiBillboard {
+    transform: mat4x4<f32>,
+    anchor: vec2<f32>,
+};
+
+// Mirrors the tightly packed `BillboardInstance`.
+struct Instance {
+    pos_x: f32,
+    pos_y: f32,
+    pos_z: f32,
+    normal_x: f32,
+    normal_y: f32,
+    normal_z: f32,
+    size_x: f32,
+    size_y: f32,
+    color_x: f32,
+    color_y: f32,
+    color_z: f32,
+    random: u32,
+};
+
+struct IndirectArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+struct CullSettings {
+    max_distance: f32,
+};
+
+@group(0) @binding(0) var<uniform> view: View;
+@group(0) @binding(1) var<uniform> billboard: MultiBillboard;
+@group(0) @binding(2) var<storage, read> instances: array<Instance>;
+@group(0) @binding(3) var<storage, read_write> culled: array<Instance>;
+@group(0) @binding(4) var<storage, read_write> args: IndirectArgs;
+@group(0) @binding(5) var<uniform> settings: CullSettings;
+
+@compute @workgroup_size(64)
+fn cull(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    // workgroups are spread over x and y to stay under the dispatch limit
+    let index = id.x + id.y * num_workgroups.x * 64u;
+    if index >= arrayLength(&instances) {
+        return;
+    }
+
+    let instance = instances[index];
+    let pos = billboard.transform * vec4(instance.pos_x, instance.pos_y, instance.pos_z, 1.0);
+
+    if distance(pos.xyz, view.world_position) > settings.max_distance {
+        return;
+    }
+
+    // side planes of the view frustum, near and far are covered by the distance check
+    let radius = max(instance.size_x, instance.size_y);
+    let m = transpose(view.view_proj);
+    var planes = array<vec4<f32>, 4>(m[3] + m[0], m[3] - m[0], m[3] + m[1], m[3] - m[1]);
+
+    for (var i = 0; i < 4; i += 1) {
+        let plane = planes[i];
+        if dot(plane, pos) < -radius * length(plane.xyz) {
+            return;
+        }
+    }
+
+    let slot = atomicAdd(&args.instance_count, 1u);
+    culled[slot] = instance;
+}
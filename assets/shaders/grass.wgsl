@@ -95,7 +95,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
     uv.x += f32(in.random % 4u) / 4.0;
     
     let color = vec4(in.color, 1.0) * textureSample(texture, texture_sampler, uv);
-    if color.a < 0.5 {
+    if color.a < bitcast<f32>(#{ALPHA_CUTOFF_BITS}u) {
         discard;
     }
 
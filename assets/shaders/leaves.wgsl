@@ -91,7 +91,7 @@ fn vertex(vertex: Vertex) -> VertexOutput {
 @fragment
 fn fragment(in: VertexOutput, @builtin(front_facing) front_facing: bool) -> @location(0) vec4<f32> {
     let color = vec4(in.color, 1.0) * textureSample(texture, texture_sampler, in.uv);
-    if color.a < 0.5 {
+    if color.a < bitcast<f32>(#{ALPHA_CUTOFF_BITS}u) {
         discard;
     }
 
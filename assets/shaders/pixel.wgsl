@@ -11,6 +11,13 @@ struct PixelMaterial {
 @group(1) @binding(0)
 var<uniform> material: PixelMaterial;
 
+#ifdef TEXTURED
+@group(1) @binding(1)
+var texture: texture_2d<f32>;
+@group(1) @binding(2)
+var texture_sampler: sampler;
+#endif
+
 @fragment
 fn fragment(
     in: VertexOutput,
@@ -25,6 +32,11 @@ fn fragment(
     var albedo = material.color.rgb;
 #ifdef VERTEX_COLORS
     albedo *= in.color.rgb;
+#endif
+#ifdef TEXTURED
+#ifdef VERTEX_UVS
+    albedo *= textureSample(texture, texture_sampler, in.uv).rgb;
+#endif
 #endif
     albedo = mix(albedo, albedo * 0.5, f32(is_edge));
 
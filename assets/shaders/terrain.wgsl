@@ -49,6 +49,8 @@ fn fragment(
         albedo = mix(wall_color, albedo, f32(fac > bayer));
     };
 
+    albedo *= 1.0 - 0.5 * in.color.y;
+
     var pixel_input: pixel::PixelInput;
     pixel_input.frag_coord = in.position;
     pixel_input.mesh_position = in.world_position;
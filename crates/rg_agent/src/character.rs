@@ -10,7 +10,7 @@ use rg_core::material::{GlobalFogHeight, PixelMaterial, ReplaceStandardMaterial}
 use rg_core::{CameraController, CoreSystems, PrevTransform};
 
 use crate::movement::MovementBundle;
-use crate::MovementInput;
+use crate::{Interact, MovementInput};
 
 pub struct CharacterPlugin;
 
@@ -78,25 +78,67 @@ pub struct CharacterModel(pub Entity);
 #[derive(Component)]
 pub struct CharacterAnimationPlayer(pub Entity);
 
+/// Tilts the character model toward the ground normal, up to `max_tilt` radians.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct SlopeAlignment {
+    pub max_tilt: f32,
+}
+
+impl Default for SlopeAlignment {
+    fn default() -> Self {
+        SlopeAlignment {
+            max_tilt: 15f32.to_radians(),
+        }
+    }
+}
+
+/// Capsule size of a character. Insert next to `SpawnCharacter` to override
+/// the default; navigation `agent_height`/`agent_radius` should match it.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct CharacterDimensions {
+    pub height: f32,
+    pub radius: f32,
+}
+
+impl Default for CharacterDimensions {
+    fn default() -> Self {
+        CharacterDimensions {
+            height: 1.8,
+            radius: 0.3,
+        }
+    }
+}
+
+impl CharacterDimensions {
+    pub fn half_height(&self) -> f32 {
+        (self.height * 0.5 - self.radius).max(0.0)
+    }
+
+    pub fn collider(&self) -> Collider {
+        Collider::capsule_z(self.half_height(), self.radius)
+    }
+}
+
 fn spawn_character(
-    q_character: Query<(Entity, &Transform), With<SpawnCharacter>>,
+    q_character: Query<(Entity, &Transform, Option<&CharacterDimensions>), With<SpawnCharacter>>,
     mut q_camera: Query<&mut CameraController>,
     mut commands: Commands,
     prototype: Res<CharacterPrototype>,
 ) {
-    let height = 1.8;
-    let radius = 0.3;
     let offset = 0.01;
 
-    for (character, &transform) in &q_character {
+    for (character, &transform, dimensions) in &q_character {
+        let dimensions = dimensions.copied().unwrap_or_default();
+
         commands
             .entity(character)
             .remove::<SpawnCharacter>()
             .insert((
                 Name::new("Character"),
                 ControlledCharacter,
+                dimensions,
                 MovementBundle {
-                    collider: Collider::capsule_z(height * 0.5 - radius, radius),
+                    collider: dimensions.collider(),
                     transform,
                     ..default()
                 },
@@ -109,6 +151,7 @@ fn spawn_character(
             .spawn((
                 Name::new("Character Model"),
                 CharacterModel(character),
+                SlopeAlignment::default(),
                 transform,
                 GlobalTransform::default(),
                 VisibilityBundle::default(),
@@ -118,7 +161,7 @@ fn spawn_character(
                 commands.spawn((
                     SceneBundle {
                         scene: prototype.scene.clone(),
-                        transform: Transform::from_xyz(0.0, 0.0, -height * 0.5 - offset),
+                        transform: Transform::from_xyz(0.0, 0.0, -dimensions.height * 0.5 - offset),
                         ..default()
                     },
                     ReplaceStandardMaterial(prototype.material.clone()),
@@ -150,11 +193,12 @@ fn find_animation_player(
 }
 
 fn control_character(
-    mut q_character: Query<&mut MovementInput, With<ControlledCharacter>>,
+    mut q_character: Query<(Entity, &mut MovementInput), With<ControlledCharacter>>,
     q_camera: Query<&CameraController>,
     input: Res<Input<KeyCode>>,
+    mut ev_interact: EventWriter<Interact>,
 ) {
-    let Ok(mut movement) = q_character.get_single_mut() else {
+    let Ok((character, mut movement)) = q_character.get_single_mut() else {
         return;
     };
 
@@ -181,6 +225,10 @@ fn control_character(
 
     movement.direction = dir;
     movement.jump = input.pressed(KeyCode::Space);
+
+    if input.just_pressed(KeyCode::F) {
+        ev_interact.send(Interact(character));
+    }
 }
 
 fn update_rotation(mut q_agents: Query<(&mut Transform, &PrevTransform), Without<CharacterModel>>) {
@@ -197,12 +245,18 @@ fn update_rotation(mut q_agents: Query<(&mut Transform, &PrevTransform), Without
 
 fn update_models(
     q_agents: Query<(&Transform, &PrevTransform), Without<CharacterModel>>,
-    mut q_models: Query<(&CharacterModel, &mut Transform, &CharacterAnimationPlayer)>,
+    mut q_models: Query<(
+        &CharacterModel,
+        &mut Transform,
+        &CharacterAnimationPlayer,
+        Option<&SlopeAlignment>,
+    )>,
     mut q_animation_player: Query<&mut AnimationPlayer>,
+    physics_context: Res<RapierContext>,
     time: Res<Time>,
     prototype: Res<CharacterPrototype>,
 ) {
-    for (model, mut model_transform, animation_player) in q_models.iter_mut() {
+    for (model, mut model_transform, animation_player, slope_alignment) in q_models.iter_mut() {
         let agent = model.0;
         let Ok((agent_transform, agent_prev_transform)) = q_agents.get(agent) else {
             continue;
@@ -215,17 +269,19 @@ fn update_models(
         let agent_dir = agent_transform.translation - agent_prev_transform.translation;
         let agent_velocity = agent_dir.xy().length() / time.delta_seconds();
 
-        if agent_velocity < 0.1 {
+        let weight = running_blend_weight(agent_velocity, RUNNING_SPEED);
+        let transition = Duration::from_millis(200);
+
+        // Bevy can only crossfade between two clips on a switch, so the
+        // running clip's playback speed follows the blend weight instead
+        if weight < MIN_RUNNING_WEIGHT {
             animation_player
-                .play_with_transition(prototype.idle_animation.clone(), Duration::from_millis(200))
+                .play_with_transition(prototype.idle_animation.clone(), transition)
                 .repeat();
         } else {
             animation_player
-                .play_with_transition(
-                    prototype.running_animation.clone(),
-                    Duration::from_millis(200),
-                )
-                .set_speed(2.0)
+                .play_with_transition(prototype.running_animation.clone(), transition)
+                .set_speed(1.0 + weight)
                 .repeat();
         }
 
@@ -234,13 +290,50 @@ fn update_models(
             .translation
             .lerp(agent_transform.translation, alpha);
 
+        let mut target_rotation = agent_transform.rotation;
+
+        if let Some(slope_alignment) = slope_alignment {
+            if let Some((_, hit)) = physics_context.cast_ray_and_get_normal(
+                agent_transform.translation,
+                -Vec3::Z,
+                2.0,
+                false,
+                QueryFilter::new().exclude_collider(agent),
+            ) {
+                target_rotation =
+                    slope_tilt(hit.normal, slope_alignment.max_tilt) * target_rotation;
+            }
+        }
+
         let alpha = 1.0 - 0.0001f32.powf(time.delta_seconds());
-        model_transform.rotation = model_transform
-            .rotation
-            .slerp(agent_transform.rotation, alpha);
+        model_transform.rotation = model_transform.rotation.slerp(target_rotation, alpha);
     }
 }
 
+const RUNNING_SPEED: f32 = 6.0;
+const MIN_RUNNING_WEIGHT: f32 = 0.01;
+
+/// Blend weight of the running animation: 0 at rest, 1 at full speed.
+pub fn running_blend_weight(speed: f32, max_speed: f32) -> f32 {
+    let t = (speed / max_speed).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn slope_tilt(normal: Vec3, max_tilt: f32) -> Quat {
+    let normal = normal.normalize_or_zero();
+    if normal == Vec3::ZERO {
+        return Quat::IDENTITY;
+    }
+
+    let tilt = Quat::from_rotation_arc(Vec3::Z, normal);
+    let (axis, angle) = tilt.to_axis_angle();
+    if angle <= max_tilt {
+        return tilt;
+    }
+
+    Quat::from_axis_angle(axis, max_tilt)
+}
+
 fn update_chunk_spawning_center(
     q_character: Query<&Transform, With<ControlledCharacter>>,
     mut center: ResMut<ChunkSpawnCenter>,
@@ -274,3 +367,74 @@ fn update_fog_height(q_camera: Query<&CameraController>, mut fog_height: ResMut<
 
     fog_height.0 = camera.translation.z;
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn running_blend_weight_endpoints() {
+        assert_eq!(running_blend_weight(0.0, RUNNING_SPEED), 0.0);
+        assert_eq!(running_blend_weight(RUNNING_SPEED, RUNNING_SPEED), 1.0);
+        assert_eq!(
+            running_blend_weight(RUNNING_SPEED * 2.0, RUNNING_SPEED),
+            1.0
+        );
+    }
+
+    #[test]
+    fn running_blend_weight_is_monotonic() {
+        let weights = (0..=60)
+            .map(|i| running_blend_weight(i as f32 * 0.1, RUNNING_SPEED))
+            .collect::<Vec<_>>();
+
+        assert!(weights.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn slope_tilt_is_clamped() {
+        let max_tilt = 20f32.to_radians();
+
+        let gentle = Vec3::new(0.0, 0.1, 1.0);
+        let tilt = slope_tilt(gentle, max_tilt);
+        assert!((tilt * Vec3::Z).angle_between(gentle.normalize()) < 1e-4);
+
+        let steep = Vec3::new(1.0, 0.0, 0.5);
+        let tilt = slope_tilt(steep, max_tilt);
+        assert!((tilt.angle_between(Quat::IDENTITY) - max_tilt).abs() < 1e-4);
+        assert!((tilt * Vec3::Z).x > 0.0);
+
+        assert_eq!(slope_tilt(Vec3::ZERO, max_tilt), Quat::IDENTITY);
+    }
+
+    #[test]
+    fn spawned_collider_matches_dimensions() {
+        let mut world = World::new();
+        world.insert_resource(CharacterPrototype {
+            scene: Handle::default(),
+            material: Handle::default(),
+            idle_animation: Handle::default(),
+            running_animation: Handle::default(),
+        });
+
+        let dimensions = CharacterDimensions {
+            height: 1.2,
+            radius: 0.4,
+        };
+        let custom = world
+            .spawn((SpawnCharacter, Transform::default(), dimensions))
+            .id();
+        let fallback = world.spawn((SpawnCharacter, Transform::default())).id();
+
+        world.run_system_once(spawn_character);
+
+        for (character, half_height, radius) in [(custom, 0.2, 0.4), (fallback, 0.6, 0.3)] {
+            let collider = world.get::<Collider>(character).unwrap();
+            let capsule = collider.as_capsule().unwrap();
+            assert!((capsule.half_height() - half_height).abs() < 1e-5);
+            assert!((capsule.radius() - radius).abs() < 1e-5);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Damage>()
+            .add_event::<Died>()
+            .add_systems(Update, apply_damage);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Component)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Health {
+        Health { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Event)]
+pub struct Damage {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+#[derive(Copy, Clone, Debug, Event)]
+pub struct Died(pub Entity);
+
+fn apply_damage(
+    mut q_health: Query<&mut Health>,
+    mut ev_damage: EventReader<Damage>,
+    mut ev_died: EventWriter<Died>,
+) {
+    for damage in ev_damage.read() {
+        let Ok(mut health) = q_health.get_mut(damage.target) else {
+            continue;
+        };
+
+        if health.is_dead() {
+            continue;
+        }
+
+        health.current = (health.current - damage.amount).clamp(0.0, health.max);
+
+        if health.is_dead() {
+            ev_died.send(Died(damage.target));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::ManualEventReader;
+
+    use super::*;
+
+    fn setup() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_plugins(HealthPlugin);
+        let target = app.world.spawn(Health::new(100.0)).id();
+        (app, target)
+    }
+
+    fn damage(app: &mut App, target: Entity, amount: f32) {
+        app.world.send_event(Damage { target, amount });
+    }
+
+    fn read_died(app: &App, reader: &mut ManualEventReader<Died>) -> Vec<Entity> {
+        let events = app.world.resource::<Events<Died>>();
+        reader.read(events).map(|died| died.0).collect()
+    }
+
+    #[test]
+    fn overkill_damage_clamps_at_zero() {
+        let (mut app, target) = setup();
+
+        damage(&mut app, target, 250.0);
+        app.update();
+
+        let health = app.world.get::<Health>(target).unwrap();
+        assert_eq!(health.current, 0.0);
+        assert!(health.is_dead());
+    }
+
+    #[test]
+    fn died_fires_once() {
+        let (mut app, target) = setup();
+        let mut reader = ManualEventReader::<Died>::default();
+        let mut died = Vec::new();
+
+        damage(&mut app, target, 60.0);
+        damage(&mut app, target, 60.0);
+        damage(&mut app, target, 60.0);
+        app.update();
+        died.extend(read_died(&app, &mut reader));
+
+        damage(&mut app, target, 10.0);
+        app.update();
+        died.extend(read_died(&app, &mut reader));
+
+        assert_eq!(died, [target]);
+    }
+}
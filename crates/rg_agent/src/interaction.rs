@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Interact>()
+            .add_event::<Interacted>()
+            .add_systems(Update, handle_interactions);
+    }
+}
+
+#[derive(Copy, Clone, Debug, Component)]
+pub struct Interactable;
+
+/// Sent by whatever controls the agent to make it use what's in front of it.
+#[derive(Copy, Clone, Debug, Event)]
+pub struct Interact(pub Entity);
+
+#[derive(Copy, Clone, Debug, Event)]
+pub struct Interacted {
+    pub agent: Entity,
+    pub target: Entity,
+}
+
+const MAX_INTERACTION_DISTANCE: f32 = 2.0;
+
+fn handle_interactions(
+    q_agents: Query<&Transform>,
+    q_interactable: Query<(), With<Interactable>>,
+    q_parent: Query<&Parent>,
+    physics_context: Res<RapierContext>,
+    mut ev_interact: EventReader<Interact>,
+    mut ev_interacted: EventWriter<Interacted>,
+) {
+    for &Interact(agent) in ev_interact.read() {
+        let Ok(transform) = q_agents.get(agent) else {
+            continue;
+        };
+
+        let Some((hit, _)) = physics_context.cast_ray(
+            transform.translation,
+            agent_forward(transform.rotation),
+            MAX_INTERACTION_DISTANCE,
+            true,
+            QueryFilter::new().exclude_collider(agent),
+        ) else {
+            continue;
+        };
+
+        if let Some(target) = select_target(hit, &q_interactable, &q_parent) {
+            ev_interacted.send(Interacted { agent, target });
+        }
+    }
+}
+
+/// Agents face -Y, `update_rotation` turns them so that it points along
+/// their velocity.
+fn agent_forward(rotation: Quat) -> Vec3 {
+    rotation * Vec3::NEG_Y
+}
+
+/// The hit collider itself, or its closest interactable ancestor.
+fn select_target(
+    hit: Entity,
+    q_interactable: &Query<(), With<Interactable>>,
+    q_parent: &Query<&Parent>,
+) -> Option<Entity> {
+    std::iter::once(hit)
+        .chain(q_parent.iter_ancestors(hit))
+        .find(|&entity| q_interactable.contains(entity))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn select(world: &mut World, hit: Entity) -> Option<Entity> {
+        world.run_system_once(
+            move |q_interactable: Query<(), With<Interactable>>, q_parent: Query<&Parent>| {
+                select_target(hit, &q_interactable, &q_parent)
+            },
+        )
+    }
+
+    #[test]
+    fn selects_closest_interactable_ancestor() {
+        let mut world = World::new();
+
+        let mut collider = Entity::PLACEHOLDER;
+        let outer = world
+            .spawn(Interactable)
+            .with_children(|parent| {
+                parent.spawn(Interactable).with_children(|parent| {
+                    collider = parent.spawn_empty().id();
+                });
+            })
+            .id();
+        let inner = world.get::<Parent>(collider).unwrap().get();
+        let plain = world.spawn_empty().id();
+
+        assert_eq!(select(&mut world, collider), Some(inner));
+        assert_eq!(select(&mut world, inner), Some(inner));
+        assert_eq!(select(&mut world, outer), Some(outer));
+        assert_eq!(select(&mut world, plain), None);
+    }
+
+    #[test]
+    fn forward_follows_velocity() {
+        // the rotation `update_rotation` gives an agent moving along +X
+        let velocity = Vec2::X;
+        let rotation = Quat::from_rotation_z(velocity.y.atan2(velocity.x) + 0.5 * PI);
+        assert!(agent_forward(rotation).abs_diff_eq(Vec3::X, 1e-6));
+    }
+}
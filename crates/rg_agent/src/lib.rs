@@ -1,15 +1,28 @@
 mod character;
+mod health;
+mod interaction;
 mod movement;
+mod spatial;
 
 use bevy::prelude::*;
 
-pub use crate::character::{CharacterPlugin, ControlledCharacter, SpawnCharacter};
-pub use crate::movement::{MovementInput, MovementPlugin};
+pub use crate::character::{
+    running_blend_weight, CharacterDimensions, CharacterPlugin, ControlledCharacter,
+    SlopeAlignment, SpawnCharacter,
+};
+pub use crate::health::{Damage, Died, Health, HealthPlugin};
+pub use crate::interaction::{Interact, Interactable, Interacted, InteractionPlugin};
+pub use crate::movement::{MovementInput, MovementPlugin, StepUp};
+pub use crate::spatial::{NearbyAgents, SpatialPlugin};
 
 pub struct AgentPlugin;
 
 impl Plugin for AgentPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MovementPlugin).add_plugins(CharacterPlugin);
+        app.add_plugins(MovementPlugin)
+            .add_plugins(CharacterPlugin)
+            .add_plugins(HealthPlugin)
+            .add_plugins(InteractionPlugin)
+            .add_plugins(SpatialPlugin);
     }
 }
@@ -17,6 +17,7 @@ impl Plugin for MovementPlugin {
 pub struct MovementBundle {
     pub movement_input: MovementInput,
     pub movement_state: MovementState,
+    pub step_up: StepUp,
     pub transform: Transform,
     pub global_transform: GlobalTransform,
     pub rigid_body: RigidBody,
@@ -34,6 +35,7 @@ impl Default for MovementBundle {
         Self {
             movement_input: MovementInput::default(),
             movement_state: MovementState::default(),
+            step_up: StepUp::default(),
             transform: Transform::default(),
             global_transform: GlobalTransform::default(),
             rigid_body: RigidBody::KinematicPositionBased,
@@ -60,11 +62,30 @@ pub struct MovementState {
     pub jump_time: f32,
 }
 
+/// Ledges up to `step_height` tall are climbed without jumping.
+#[derive(Copy, Clone, Debug, Component)]
+pub struct StepUp {
+    pub step_height: f32,
+}
+
+impl Default for StepUp {
+    fn default() -> Self {
+        Self { step_height: 0.3 }
+    }
+}
+
+impl StepUp {
+    pub fn can_step_up(&self, obstacle_height: f32) -> bool {
+        obstacle_height > 0.0 && obstacle_height <= self.step_height
+    }
+}
+
 fn handle_movement_input(
     mut q_agents: Query<(
         Entity,
         &MovementInput,
         &mut MovementState,
+        &StepUp,
         &Collider,
         &mut Transform,
     )>,
@@ -75,7 +96,6 @@ fn handle_movement_input(
 
     // TODO
     let offset = 0.01;
-    let step_height = 0.3;
     let gravity = 30.0;
     let speed = 6.0;
     let jump_velocity = 5.0;
@@ -84,7 +104,7 @@ fn handle_movement_input(
     let air_acceleration = 30.0;
     let ground_acceleration = 300.0;
 
-    for (entity, input, mut state, collider, mut transform) in &mut q_agents {
+    for (entity, input, mut state, step_up, collider, mut transform) in &mut q_agents {
         let mut position = transform.translation;
         let mut velocity = state.velocity;
 
@@ -166,6 +186,8 @@ fn handle_movement_input(
             state.jump_time = 0.0;
         }
 
+        let step_height = step_up.step_height;
+
         if enable_stepping {
             // cast up
             move_and_stop(&mut position, Vec3::Z, step_height);
@@ -178,6 +200,13 @@ fn handle_movement_input(
             // cast down
             let limit = position.z - prev_position.z + step_height;
             move_and_stop(&mut position, -Vec3::Z, limit);
+
+            // sliding along steep walls can lift us higher than a step
+            let climbed = position.z - prev_position.z;
+            if climbed > offset && !step_up.can_step_up(climbed) {
+                position = prev_position;
+                move_and_slide(&mut position, velocity * dt);
+            }
         }
 
         let translation = position - prev_position;
@@ -187,3 +216,19 @@ fn handle_movement_input(
         transform.translation = position;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_step_up() {
+        let step_up = StepUp { step_height: 0.3 };
+
+        assert!(step_up.can_step_up(0.1));
+        assert!(step_up.can_step_up(0.3));
+        assert!(!step_up.can_step_up(0.31));
+        assert!(!step_up.can_step_up(0.0));
+        assert!(!step_up.can_step_up(-0.2));
+    }
+}
@@ -0,0 +1,107 @@
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+use crate::movement::MovementState;
+
+pub struct SpatialPlugin;
+
+impl Plugin for SpatialPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NearbyAgents::new(8.0))
+            .add_systems(PreUpdate, update_nearby_agents);
+    }
+}
+
+/// Spatial hash of agent positions, rebuilt every frame.
+#[derive(Debug, Resource)]
+pub struct NearbyAgents {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Entity, Vec2)>>,
+}
+
+impl NearbyAgents {
+    pub fn new(cell_size: f32) -> NearbyAgents {
+        NearbyAgents {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for agents in self.cells.values_mut() {
+            agents.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entity: Entity, pos: Vec2) {
+        let cell = self.pos_to_cell(pos);
+        self.cells.entry(cell).or_default().push((entity, pos));
+    }
+
+    pub fn query_radius(&self, pos: Vec2, radius: f32) -> impl Iterator<Item = Entity> + '_ {
+        let min = self.pos_to_cell(pos - radius);
+        let max = self.pos_to_cell(pos + radius);
+        let radius_squared = radius * radius;
+
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |(_, agent_pos)| agent_pos.distance_squared(pos) <= radius_squared)
+            .map(|&(entity, _)| entity)
+    }
+
+    fn pos_to_cell(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+}
+
+fn update_nearby_agents(
+    q_agents: Query<(Entity, &Transform), With<MovementState>>,
+    mut nearby_agents: ResMut<NearbyAgents>,
+) {
+    nearby_agents.clear();
+
+    for (entity, transform) in &q_agents {
+        nearby_agents.insert(entity, transform.translation.xy());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn query_radius_is_exact() {
+        let mut nearby_agents = NearbyAgents::new(8.0);
+        let mut agents = Vec::new();
+
+        for i in 0..400 {
+            let entity = Entity::from_raw(i);
+            let pos = Vec2::new((i % 20) as f32, (i / 20) as f32) * 2.3 - 20.0;
+            nearby_agents.insert(entity, pos);
+            agents.push((entity, pos));
+        }
+
+        for (center, radius) in [
+            (Vec2::ZERO, 5.0),
+            (Vec2::new(-13.1, 7.4), 9.5),
+            (Vec2::new(3.0, -20.0), 0.5),
+            (Vec2::new(100.0, 100.0), 10.0),
+        ] {
+            let found = nearby_agents
+                .query_radius(center, radius)
+                .collect::<HashSet<_>>();
+            let expected = agents
+                .iter()
+                .filter(|(_, pos)| pos.distance_squared(center) <= radius * radius)
+                .map(|&(entity, _)| entity)
+                .collect::<HashSet<_>>();
+
+            assert_eq!(found, expected, "{center} {radius}");
+        }
+    }
+}
@@ -11,6 +11,7 @@ impl Plugin for DefaultActionsPlugin {
     fn build(&self, app: &mut App) {
         app.add_action::<SequenceUntilFailure>()
             .add_action::<SequenceUntilSuccess>()
+            .add_action::<Parallel>()
             .add_action::<AlwaysSucceed>()
             .add_action::<AlwaysFail>()
             .add_action::<InvertResult>()
@@ -81,6 +82,95 @@ fn process_sequence_until_success(mut q_agents: Query<&mut Behavior<SequenceUnti
     }
 }
 
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Reflect)]
+pub enum ParallelPolicy {
+    #[default]
+    RequireAll,
+    RequireOne,
+}
+
+impl ParallelPolicy {
+    fn is_met(self, count: usize, num_children: usize) -> bool {
+        match self {
+            ParallelPolicy::RequireAll => count == num_children,
+            ParallelPolicy::RequireOne => count > 0,
+        }
+    }
+}
+
+/// Runs children one per tick, finishing once enough of them have succeeded
+/// or failed according to the policies.
+#[derive(Clone, Reflect)]
+pub struct Parallel {
+    pub success_policy: ParallelPolicy,
+    pub failure_policy: ParallelPolicy,
+    index: usize,
+    num_succeeded: usize,
+    num_failed: usize,
+}
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Self {
+            success_policy: ParallelPolicy::RequireAll,
+            failure_policy: ParallelPolicy::RequireOne,
+            index: 0,
+            num_succeeded: 0,
+            num_failed: 0,
+        }
+    }
+}
+
+impl Parallel {
+    /// `Some(true)` on success, `Some(false)` on failure, `None` while
+    /// children still have to run.
+    fn outcome(&self, num_children: usize) -> Option<bool> {
+        let succeeded = self.success_policy.is_met(self.num_succeeded, num_children);
+        // once a child fails, RequireAll can no longer succeed
+        let failed = self.failure_policy.is_met(self.num_failed, num_children)
+            || (self.success_policy == ParallelPolicy::RequireAll && self.num_failed > 0)
+            || (self.success_policy == ParallelPolicy::RequireOne
+                && self.num_failed == num_children);
+
+        if succeeded {
+            Some(true)
+        } else if failed {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl Action for Parallel {
+    fn register(app: &mut App) {
+        app.add_systems(Update, process_parallel.in_set(BehaviorTreeSystem::Process));
+    }
+}
+
+fn process_parallel(mut q_agents: Query<&mut Behavior<Parallel>>) {
+    for mut behavior in &mut q_agents {
+        if behavior.has_returned_from_child() {
+            if behavior.child_failed() {
+                behavior.action.num_failed += 1;
+            } else {
+                behavior.action.num_succeeded += 1;
+            }
+        }
+
+        let num_children = behavior.num_children();
+        match behavior.action.outcome(num_children) {
+            Some(true) => behavior.success(),
+            Some(false) => behavior.failure(),
+            None => {
+                let index = behavior.action.index;
+                behavior.action.index = (index + 1) % num_children;
+                behavior.run_child(index);
+            }
+        }
+    }
+}
+
 #[derive(Default, Clone, Reflect)]
 pub struct AlwaysSucceed;
 
@@ -196,3 +286,48 @@ fn process_log_message(mut q_agents: Query<(Entity, &mut Behavior<LogMessage>)>)
         behavior.success();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parallel(success_policy: ParallelPolicy, failure_policy: ParallelPolicy) -> Parallel {
+        Parallel {
+            success_policy,
+            failure_policy,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn require_all_fails_on_first_failure() {
+        let mut action = parallel(ParallelPolicy::RequireAll, ParallelPolicy::RequireAll);
+        assert_eq!(action.outcome(3), None);
+
+        action.num_succeeded = 1;
+        assert_eq!(action.outcome(3), None);
+
+        action.num_failed = 1;
+        assert_eq!(action.outcome(3), Some(false));
+    }
+
+    #[test]
+    fn require_all_succeeds_once_all_succeeded() {
+        let mut action = parallel(ParallelPolicy::RequireAll, ParallelPolicy::RequireOne);
+        action.num_succeeded = 2;
+        assert_eq!(action.outcome(3), None);
+
+        action.num_succeeded = 3;
+        assert_eq!(action.outcome(3), Some(true));
+    }
+
+    #[test]
+    fn require_one_fails_once_all_failed() {
+        let mut action = parallel(ParallelPolicy::RequireOne, ParallelPolicy::RequireAll);
+        action.num_failed = 2;
+        assert_eq!(action.outcome(3), None);
+
+        action.num_failed = 3;
+        assert_eq!(action.outcome(3), Some(false));
+    }
+}
@@ -0,0 +1,416 @@
+use bevy::core::{bytes_of, Pod, Zeroable};
+use bevy::core_pipeline::core_3d;
+use bevy::core_pipeline::core_3d::AlphaMask3d;
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::extract_component::{ComponentUniforms, DynamicUniformIndex};
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{
+    NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner,
+};
+use bevy::render::render_phase::RenderPhase;
+use bevy::render::render_resource::{
+    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor, BindGroupLayoutEntry,
+    BindingType, Buffer, BufferBindingType, BufferDescriptor, BufferUsages,
+    CachedComputePipelineId, ComputePassDescriptor, ComputePipelineDescriptor, PipelineCache,
+    ShaderStages, ShaderType, UniformBuffer,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy::render::view::{ViewUniform, ViewUniformOffset, ViewUniforms, VisibleEntities};
+use bevy::render::{Render, RenderApp, RenderSet};
+use bevy::utils::HashMap;
+
+use super::instance::MultiBillboardUniform;
+use super::{BillboardInstance, MultiBillboard};
+
+const WORKGROUP_SIZE: u32 = 64;
+const MAX_WORKGROUPS_PER_DIMENSION: u32 = 65535;
+const BILLBOARD_CULL: &str = "billboard_cull";
+
+/// Culls billboard instances on the GPU before drawing them. Disabled by
+/// default, every instance of a visible `MultiBillboard` is drawn then.
+#[derive(Debug, Clone, Copy, Resource, ExtractResource)]
+pub struct BillboardCulling {
+    pub enabled: bool,
+    pub max_distance: f32,
+}
+
+impl Default for BillboardCulling {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_distance: 200.0,
+        }
+    }
+}
+
+pub struct BillboardCullingPlugin;
+
+impl Plugin for BillboardCullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BillboardCulling>()
+            .add_plugins(ExtractResourcePlugin::<BillboardCulling>::default());
+
+        app.sub_app_mut(RenderApp)
+            .init_resource::<BillboardCullBuffers>()
+            .add_systems(
+                Render,
+                prepare_cull_buffers
+                    .in_set(RenderSet::PrepareBindGroups)
+                    .run_if(resource_exists::<BillboardCulling>()),
+            )
+            .add_render_graph_node::<ViewNodeRunner<BillboardCullNode>>(
+                core_3d::graph::NAME,
+                BILLBOARD_CULL,
+            )
+            .add_render_graph_edges(
+                core_3d::graph::NAME,
+                &[BILLBOARD_CULL, core_3d::graph::node::PREPASS],
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        app.sub_app_mut(RenderApp)
+            .init_resource::<BillboardCullPipeline>();
+    }
+}
+
+/// Arguments of `draw_indexed_indirect`, as laid out by wgpu.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Pod, Zeroable)]
+#[repr(C)]
+pub struct DrawIndexedIndirectArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
+}
+
+impl DrawIndexedIndirectArgs {
+    pub fn billboards(num_instances: u32) -> DrawIndexedIndirectArgs {
+        DrawIndexedIndirectArgs {
+            index_count: 6,
+            instance_count: num_instances,
+            ..default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, ShaderType)]
+struct CullSettingsUniform {
+    max_distance: f32,
+}
+
+#[derive(Resource)]
+pub struct BillboardCullPipeline {
+    layout: BindGroupLayout,
+    pipeline_id: CachedComputePipelineId,
+}
+
+impl FromWorld for BillboardCullPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let asset_server = world.resource::<AssetServer>();
+
+        let uniform = |binding, min_binding_size, has_dynamic_offset| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset,
+                min_binding_size: Some(min_binding_size),
+            },
+            count: None,
+        };
+
+        let storage = |binding, read_only| BindGroupLayoutEntry {
+            binding,
+            visibility: ShaderStages::COMPUTE,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("billboard_cull_layout"),
+            entries: &[
+                uniform(0, ViewUniform::min_size(), true),
+                uniform(1, MultiBillboardUniform::min_size(), true),
+                storage(2, true),
+                storage(3, false),
+                storage(4, false),
+                uniform(5, CullSettingsUniform::min_size(), false),
+            ],
+        });
+
+        let shader = asset_server.load("shaders/billboard_cull.wgsl");
+
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = pipeline_cache.queue_compute_pipeline(ComputePipelineDescriptor {
+            label: Some("billboard_cull".into()),
+            layout: vec![layout.clone()],
+            push_constant_ranges: Vec::new(),
+            shader,
+            shader_defs: Vec::new(),
+            entry_point: "cull".into(),
+        });
+
+        BillboardCullPipeline {
+            layout,
+            pipeline_id,
+        }
+    }
+}
+
+pub struct CulledBillboard {
+    pub instance_buffer: Buffer,
+    pub indirect_buffer: Buffer,
+    capacity: u32,
+    num_instances: u32,
+    uniform_index: u32,
+    bind_group: Option<BindGroup>,
+}
+
+/// Culled instances of every visible `MultiBillboard`, keyed by view and
+/// billboard entity.
+#[derive(Default, Resource)]
+pub struct BillboardCullBuffers {
+    map: HashMap<(Entity, Entity), CulledBillboard>,
+    settings: UniformBuffer<CullSettingsUniform>,
+}
+
+impl BillboardCullBuffers {
+    pub fn get(&self, view: Entity, entity: Entity) -> Option<&CulledBillboard> {
+        self.map.get(&(view, entity))
+    }
+}
+
+fn prepare_cull_buffers(
+    q_views: Query<(Entity, &VisibleEntities), With<RenderPhase<AlphaMask3d>>>,
+    q_multi_billboards: Query<(
+        &Handle<MultiBillboard>,
+        &DynamicUniformIndex<MultiBillboardUniform>,
+    )>,
+    multi_billboards: Res<RenderAssets<MultiBillboard>>,
+    culling: Res<BillboardCulling>,
+    pipeline: Res<BillboardCullPipeline>,
+    pipeline_cache: Res<PipelineCache>,
+    view_uniforms: Res<ViewUniforms>,
+    billboard_uniforms: Res<ComponentUniforms<MultiBillboardUniform>>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+    mut buffers: ResMut<BillboardCullBuffers>,
+) {
+    let buffers = &mut *buffers;
+
+    let ready = pipeline_cache
+        .get_compute_pipeline(pipeline.pipeline_id)
+        .is_some();
+
+    if !culling.enabled || !ready {
+        buffers.map.clear();
+        return;
+    }
+
+    let (Some(view_binding), Some(billboard_binding)) = (
+        view_uniforms.uniforms.binding(),
+        billboard_uniforms.uniforms().binding(),
+    ) else {
+        buffers.map.clear();
+        return;
+    };
+
+    buffers.settings.set(CullSettingsUniform {
+        max_distance: culling.max_distance,
+    });
+    buffers.settings.write_buffer(&render_device, &render_queue);
+
+    let Some(settings_binding) = buffers.settings.binding() else {
+        buffers.map.clear();
+        return;
+    };
+
+    let mut old_map = std::mem::take(&mut buffers.map);
+
+    for (view, visible_entities) in &q_views {
+        for &entity in &visible_entities.entities {
+            let Ok((handle, uniform_index)) = q_multi_billboards.get(entity) else {
+                continue;
+            };
+
+            let Some(multi_billboard) = multi_billboards.get(handle) else {
+                continue;
+            };
+
+            if multi_billboard.num_instances == 0 {
+                continue;
+            }
+
+            let mut culled = match old_map.remove(&(view, entity)) {
+                Some(culled) if culled.capacity >= multi_billboard.num_instances => culled,
+                _ => create_culled_billboard(&render_device, multi_billboard.num_instances),
+            };
+
+            culled.num_instances = multi_billboard.num_instances;
+            culled.uniform_index = uniform_index.index();
+
+            render_queue.write_buffer(
+                &culled.indirect_buffer,
+                0,
+                bytes_of(&DrawIndexedIndirectArgs::billboards(0)),
+            );
+
+            culled.bind_group = Some(render_device.create_bind_group(
+                "billboard_cull_bind_group",
+                &pipeline.layout,
+                &[
+                    BindGroupEntry {
+                        binding: 0,
+                        resource: view_binding.clone(),
+                    },
+                    BindGroupEntry {
+                        binding: 1,
+                        resource: billboard_binding.clone(),
+                    },
+                    BindGroupEntry {
+                        binding: 2,
+                        resource: multi_billboard.instance_buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 3,
+                        resource: culled.instance_buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 4,
+                        resource: culled.indirect_buffer.as_entire_binding(),
+                    },
+                    BindGroupEntry {
+                        binding: 5,
+                        resource: settings_binding.clone(),
+                    },
+                ],
+            ));
+
+            buffers.map.insert((view, entity), culled);
+        }
+    }
+}
+
+fn create_culled_billboard(render_device: &RenderDevice, num_instances: u32) -> CulledBillboard {
+    let instance_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("Culled Billboard Instance Buffer"),
+        size: (num_instances as usize * std::mem::size_of::<BillboardInstance>()) as u64,
+        usage: BufferUsages::VERTEX | BufferUsages::STORAGE,
+        mapped_at_creation: false,
+    });
+
+    let indirect_buffer = render_device.create_buffer(&BufferDescriptor {
+        label: Some("Culled Billboard Indirect Buffer"),
+        size: std::mem::size_of::<DrawIndexedIndirectArgs>() as u64,
+        usage: BufferUsages::INDIRECT | BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    CulledBillboard {
+        instance_buffer,
+        indirect_buffer,
+        capacity: num_instances,
+        num_instances,
+        uniform_index: 0,
+        bind_group: None,
+    }
+}
+
+#[derive(Default)]
+struct BillboardCullNode;
+
+impl ViewNode for BillboardCullNode {
+    type ViewQuery = (Entity, &'static ViewUniformOffset);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view, view_uniform_offset): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let buffers = world.resource::<BillboardCullBuffers>();
+        let pipeline = world.resource::<BillboardCullPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+
+        let Some(pipeline) = pipeline_cache.get_compute_pipeline(pipeline.pipeline_id) else {
+            return Ok(());
+        };
+
+        let mut pass =
+            render_context
+                .command_encoder()
+                .begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("billboard_cull"),
+                });
+
+        pass.set_pipeline(pipeline);
+
+        for (&(culled_view, _), culled) in &buffers.map {
+            let Some(bind_group) = &culled.bind_group else {
+                continue;
+            };
+
+            if culled_view != view {
+                continue;
+            }
+
+            pass.set_bind_group(
+                0,
+                bind_group,
+                &[view_uniform_offset.offset, culled.uniform_index],
+            );
+            let (x, y) = workgroup_count(culled.num_instances);
+            pass.dispatch_workgroups(x, y, 1);
+        }
+
+        Ok(())
+    }
+}
+
+/// Spreads the workgroups over two dimensions, so that large billboards don't
+/// exceed the per-dimension dispatch limit.
+fn workgroup_count(num_instances: u32) -> (u32, u32) {
+    let num_workgroups = num_instances.div_ceil(WORKGROUP_SIZE);
+    let x = num_workgroups.min(MAX_WORKGROUPS_PER_DIMENSION);
+    let y = num_workgroups.div_ceil(MAX_WORKGROUPS_PER_DIMENSION);
+    (x, y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indirect_args_match_instance_count() {
+        let args = DrawIndexedIndirectArgs::billboards(1234);
+        assert_eq!(args.index_count, 6);
+        assert_eq!(args.instance_count, 1234);
+        assert_eq!(args.first_index, 0);
+        assert_eq!(args.base_vertex, 0);
+        assert_eq!(args.first_instance, 0);
+    }
+
+    #[test]
+    fn workgroups_cover_all_instances() {
+        assert_eq!(workgroup_count(0), (0, 0));
+        assert_eq!(workgroup_count(1), (1, 1));
+        assert_eq!(workgroup_count(64 * 100), (100, 1));
+
+        for num_instances in [64 * 65535, 64 * 65535 + 1, u32::MAX] {
+            let (x, y) = workgroup_count(num_instances);
+            assert!(x <= MAX_WORKGROUPS_PER_DIMENSION);
+            assert!(y <= MAX_WORKGROUPS_PER_DIMENSION);
+            assert!(x as u64 * y as u64 * WORKGROUP_SIZE as u64 >= num_instances as u64);
+        }
+    }
+}
@@ -12,7 +12,7 @@ use bevy::render::render_resource::{
     VertexStepMode,
 };
 use bevy::render::renderer::RenderDevice;
-use bevy::render::view::NoFrustumCulling;
+use bevy::render::view::{NoFrustumCulling, RenderLayers};
 use bevy::render::Extract;
 
 #[derive(Debug, Copy, Clone, Pod, Zeroable)]
@@ -163,7 +163,7 @@ fn create_instance_buffer(device: &RenderDevice, instances: &[BillboardInstance]
     device.create_buffer_with_data(&BufferInitDescriptor {
         label: Some("Billboard Instance Buffer"),
         contents: cast_slice(instances),
-        usage: BufferUsages::VERTEX,
+        usage: BufferUsages::VERTEX | BufferUsages::STORAGE,
     })
 }
 
@@ -180,12 +180,13 @@ pub fn extract_multi_billboards(
             &Handle<MultiBillboard>,
             &GlobalTransform,
             &ViewVisibility,
+            Option<&RenderLayers>,
         )>,
     >,
     multi_billboards: Extract<Res<Assets<MultiBillboard>>>,
     mut commands: Commands,
 ) {
-    for (entity, multi_billboard_handle, transform, visibility) in &q_multi_billboards {
+    for (entity, multi_billboard_handle, transform, visibility, layers) in &q_multi_billboards {
         if !visibility.get() {
             continue;
         }
@@ -200,6 +201,20 @@ pub fn extract_multi_billboards(
                 transform: transform.compute_matrix(),
                 anchor: multi_billboard.anchor,
             },
+            layers.copied().unwrap_or_default(),
         ));
     }
 }
+
+/// Copies camera layers onto the extracted views so billboards can be
+/// filtered per view.
+pub fn extract_view_render_layers(
+    q_cameras: Extract<Query<(Entity, &Camera, &RenderLayers)>>,
+    mut commands: Commands,
+) {
+    for (entity, camera, layers) in &q_cameras {
+        if camera.is_active {
+            commands.get_or_spawn(entity).insert(*layers);
+        }
+    }
+}
@@ -25,17 +25,18 @@ use bevy::render::render_resource::{
     BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingType, BlendState, BufferBindingType,
     ColorTargetState, ColorWrites, CompareFunction, DepthBiasState, DepthStencilState, IndexFormat,
     OwnedBindingResource, PipelineCache, PrimitiveState, PrimitiveTopology,
-    RenderPipelineDescriptor, ShaderStages, ShaderType, SpecializedMeshPipeline,
+    RenderPipelineDescriptor, ShaderDefVal, ShaderStages, ShaderType, SpecializedMeshPipeline,
     SpecializedMeshPipelineError, SpecializedMeshPipelines, StencilFaceState, StencilState,
 };
 use bevy::render::renderer::RenderDevice;
 use bevy::render::texture::FallbackImage;
 use bevy::render::view::{
-    ExtractedView, ViewUniform, ViewUniformOffset, ViewUniforms, VisibleEntities,
+    ExtractedView, RenderLayers, ViewUniform, ViewUniformOffset, ViewUniforms, VisibleEntities,
 };
 use bevy::render::{Extract, Render, RenderApp, RenderSet};
 use bevy::utils::{HashMap, HashSet};
 
+use super::culling::BillboardCullBuffers;
 use super::instance::{BillboardVertex, MultiBillboardUniform};
 use super::{BillboardInstance, MultiBillboard};
 
@@ -46,6 +47,12 @@ pub trait BillboardMaterial:
 
     fn fragment_shader() -> AssetPath<'static>;
 
+    /// Fragments with a lower alpha are discarded. Shaders get it as the bits
+    /// of an `f32` in the `ALPHA_CUTOFF_BITS` shader def.
+    fn alpha_cutoff(&self) -> f32 {
+        0.5
+    }
+
     fn specialize(
         pipeline: BillboardMaterialPipeline<Self>,
         descriptor: &mut RenderPipelineDescriptor,
@@ -130,10 +137,11 @@ pub fn queue_billboard_batches<M>(
     mut q_views: Query<(
         &ExtractedView,
         &VisibleEntities,
+        Option<&RenderLayers>,
         &mut RenderPhase<AlphaMask3dPrepass>,
         &mut RenderPhase<AlphaMask3d>,
     )>,
-    q_multi_billboards: Query<(Entity, &MultiBillboardUniform, &Handle<M>)>,
+    q_multi_billboards: Query<(Entity, &MultiBillboardUniform, &RenderLayers, &Handle<M>)>,
     main_draw_functions: Res<DrawFunctions<AlphaMask3d>>,
     prepass_draw_functions: Res<DrawFunctions<AlphaMask3dPrepass>>,
     materials: Res<PreparedBillboardMaterials<M>>,
@@ -159,12 +167,17 @@ pub fn queue_billboard_batches<M>(
     let dummy_mesh = Mesh::new(PrimitiveTopology::TriangleList);
     let mesh_layout = dummy_mesh.get_mesh_vertex_buffer_layout();
 
-    for (view, visible_entities, mut prepass_phase, mut main_phase) in &mut q_views {
+    for (view, visible_entities, view_layers, mut prepass_phase, mut main_phase) in &mut q_views {
         let mesh_key = MeshPipelineKey::from_hdr(view.hdr);
         let rangefinder = view.rangefinder3d();
 
-        for (entity, uniform, material) in q_multi_billboards.iter_many(&visible_entities.entities)
+        for (entity, uniform, layers, material) in
+            q_multi_billboards.iter_many(&visible_entities.entities)
         {
+            if !is_on_view_layers(view_layers, layers) {
+                continue;
+            }
+
             let Some(material) = materials.map.get(&material.id()) else {
                 continue;
             };
@@ -178,6 +191,7 @@ pub fn queue_billboard_batches<M>(
                     BillboardMaterialKey {
                         mesh_key,
                         bind_group_data: material.key.clone(),
+                        alpha_cutoff: material.alpha_cutoff,
                     },
                     &mesh_layout,
                 )
@@ -199,6 +213,7 @@ pub fn queue_billboard_batches<M>(
                     BillboardMaterialKey {
                         mesh_key,
                         bind_group_data: material.key.clone(),
+                        alpha_cutoff: material.alpha_cutoff,
                     },
                     &mesh_layout,
                 )
@@ -216,10 +231,22 @@ pub fn queue_billboard_batches<M>(
     }
 }
 
+/// Views without `RenderLayers` only see the default layer.
+fn is_on_view_layers(view_layers: Option<&RenderLayers>, layers: &RenderLayers) -> bool {
+    view_layers.copied().unwrap_or_default().intersects(layers)
+}
+
 #[derive(Clone)]
 pub struct BillboardMaterialKey<M: BillboardMaterial> {
     pub mesh_key: MeshPipelineKey,
     pub bind_group_data: M::Data,
+    pub alpha_cutoff: f32,
+}
+
+impl<M: BillboardMaterial> BillboardMaterialKey<M> {
+    pub fn alpha_cutoff_def(&self) -> ShaderDefVal {
+        ShaderDefVal::UInt("ALPHA_CUTOFF_BITS".into(), self.alpha_cutoff.to_bits())
+    }
 }
 
 impl<M> Eq for BillboardMaterialKey<M>
@@ -235,7 +262,9 @@ where
     M::Data: PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.mesh_key == other.mesh_key && self.bind_group_data == other.bind_group_data
+        self.mesh_key == other.mesh_key
+            && self.bind_group_data == other.bind_group_data
+            && self.alpha_cutoff.to_bits() == other.alpha_cutoff.to_bits()
     }
 }
 
@@ -247,6 +276,7 @@ where
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.mesh_key.hash(state);
         self.bind_group_data.hash(state);
+        self.alpha_cutoff.to_bits().hash(state);
     }
 }
 
@@ -320,9 +350,11 @@ where
         key: Self::Key,
         layout: &MeshVertexBufferLayout,
     ) -> Result<RenderPipelineDescriptor, SpecializedMeshPipelineError> {
+        let alpha_cutoff_def = key.alpha_cutoff_def();
         let mut descriptor = self.mesh_pipeline.specialize(key.mesh_key, layout)?;
 
         descriptor.vertex.shader = self.vertex_shader.clone();
+        descriptor.vertex.shader_defs.push(alpha_cutoff_def.clone());
         descriptor.vertex.buffers = vec![
             BillboardVertex::vertex_buffer_layout(),
             BillboardInstance::vertex_buffer_layout(),
@@ -330,7 +362,9 @@ where
         descriptor.layout.drain(1..);
         descriptor.layout.push(self.material_layout.clone());
         descriptor.layout.push(self.uniform_layout.clone());
-        descriptor.fragment.as_mut().unwrap().shader = self.fragment_shader.clone();
+        let fragment = descriptor.fragment.as_mut().unwrap();
+        fragment.shader = self.fragment_shader.clone();
+        fragment.shader_defs.push(alpha_cutoff_def);
         descriptor.primitive = PrimitiveState::default();
         descriptor.label = Some("billboard_main".into());
 
@@ -559,27 +593,35 @@ impl<const I: usize, M: BillboardMaterial, P: PhaseItem> RenderCommand<P>
 pub struct DrawBillboardBatch;
 
 impl<P: PhaseItem> RenderCommand<P> for DrawBillboardBatch {
-    type Param = SRes<RenderAssets<MultiBillboard>>;
-    type ViewWorldQuery = ();
-    type ItemWorldQuery = Read<Handle<MultiBillboard>>;
+    type Param = (
+        SRes<RenderAssets<MultiBillboard>>,
+        SRes<BillboardCullBuffers>,
+    );
+    type ViewWorldQuery = Entity;
+    type ItemWorldQuery = (Entity, Read<Handle<MultiBillboard>>);
 
     fn render<'w>(
         _item: &P,
-        _view: ROQueryItem<'w, Self::ViewWorldQuery>,
-        handle: ROQueryItem<'w, Self::ItemWorldQuery>,
-        multi_billboards: SystemParamItem<'w, '_, Self::Param>,
+        view: ROQueryItem<'w, Self::ViewWorldQuery>,
+        (entity, handle): ROQueryItem<'w, Self::ItemWorldQuery>,
+        (multi_billboards, cull_buffers): SystemParamItem<'w, '_, Self::Param>,
         pass: &mut TrackedRenderPass<'w>,
     ) -> RenderCommandResult {
         if let Some(multi_billboard) = multi_billboards.into_inner().get(handle) {
             pass.set_vertex_buffer(0, multi_billboard.vertex_buffer.slice(..));
-            pass.set_vertex_buffer(1, multi_billboard.instance_buffer.slice(..));
             pass.set_index_buffer(
                 multi_billboard.index_buffer.slice(..),
                 0,
                 IndexFormat::Uint32,
             );
 
-            pass.draw_indexed(0..6, 0, 0..multi_billboard.num_instances);
+            if let Some(culled) = cull_buffers.into_inner().get(view, entity) {
+                pass.set_vertex_buffer(1, culled.instance_buffer.slice(..));
+                pass.draw_indexed_indirect(&culled.indirect_buffer, 0);
+            } else {
+                pass.set_vertex_buffer(1, multi_billboard.instance_buffer.slice(..));
+                pass.draw_indexed(0..6, 0, 0..multi_billboard.num_instances);
+            }
 
             RenderCommandResult::Success
         } else {
@@ -645,6 +687,7 @@ pub struct PreparedBillboardMaterial<M: BillboardMaterial> {
     pub bindings: Vec<(u32, OwnedBindingResource)>,
     pub bind_group: BindGroup,
     pub key: M::Data,
+    pub alpha_cutoff: f32,
 }
 
 impl<M: BillboardMaterial> Default for PreparedBillboardMaterials<M> {
@@ -732,5 +775,60 @@ fn prepare_material<M: BillboardMaterial>(
         bindings: prepared.bindings,
         bind_group: prepared.bind_group,
         key: prepared.data,
+        alpha_cutoff: material.alpha_cutoff(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, AsBindGroup, TypeUuid, TypePath, Asset)]
+    #[uuid = "5c1e0f5a-3a4e-4d0b-9a55-0f6f2a4c6e21"]
+    struct TestMaterial {}
+
+    impl BillboardMaterial for TestMaterial {
+        fn vertex_shader() -> AssetPath<'static> {
+            "shaders/test.wgsl".into()
+        }
+
+        fn fragment_shader() -> AssetPath<'static> {
+            "shaders/test.wgsl".into()
+        }
+    }
+
+    fn key(alpha_cutoff: f32) -> BillboardMaterialKey<TestMaterial> {
+        BillboardMaterialKey {
+            mesh_key: MeshPipelineKey::NONE,
+            bind_group_data: (),
+            alpha_cutoff,
+        }
+    }
+
+    #[test]
+    fn alpha_cutoff_is_part_of_key() {
+        assert!(key(0.5) == key(0.5));
+        assert!(key(0.5) != key(0.25));
+
+        let keys = [key(0.5), key(0.5), key(0.25)]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(keys.len(), 2);
+
+        assert_eq!(
+            key(0.25).alpha_cutoff_def(),
+            ShaderDefVal::UInt("ALPHA_CUTOFF_BITS".into(), 0.25f32.to_bits())
+        );
+    }
+
+    #[test]
+    fn billboard_on_other_layer_is_skipped() {
+        let camera_layers = RenderLayers::from_layers(&[0, 1]);
+        let view = Some(&camera_layers);
+
+        assert!(is_on_view_layers(view, &RenderLayers::layer(1)));
+        assert!(!is_on_view_layers(view, &RenderLayers::layer(2)));
+        assert!(is_on_view_layers(None, &RenderLayers::default()));
+        assert!(!is_on_view_layers(None, &RenderLayers::layer(1)));
+    }
+}
@@ -1,6 +1,7 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 
+mod culling;
 mod instance;
 mod material;
 mod scatter;
@@ -8,11 +9,13 @@ mod scatter;
 use bevy::prelude::*;
 use bevy::render::extract_component::UniformComponentPlugin;
 use bevy::render::render_asset::RenderAssetPlugin;
-use bevy::render::view::VisibilitySystems;
+use bevy::render::view::{RenderLayers, VisibilitySystems};
 use bevy::render::RenderApp;
 
+pub use self::culling::{BillboardCulling, BillboardCullingPlugin, DrawIndexedIndirectArgs};
 use self::instance::{
-    compute_multi_billboard_bounds, extract_multi_billboards, MultiBillboardUniform,
+    compute_multi_billboard_bounds, extract_multi_billboards, extract_view_render_layers,
+    MultiBillboardUniform,
 };
 pub use self::instance::{BillboardInstance, MultiBillboard};
 pub use self::material::{BillboardMaterial, BillboardMaterialKey, BillboardMaterialPlugin};
@@ -22,7 +25,7 @@ pub struct BillboardPlugin;
 
 impl Plugin for BillboardPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(ScatterPlugin)
+        app.add_plugins((ScatterPlugin, BillboardCullingPlugin))
             .init_asset::<MultiBillboard>()
             .add_plugins(RenderAssetPlugin::<MultiBillboard>::default())
             .add_plugins(UniformComponentPlugin::<MultiBillboardUniform>::default())
@@ -31,8 +34,10 @@ impl Plugin for BillboardPlugin {
                 compute_multi_billboard_bounds.in_set(VisibilitySystems::CalculateBounds),
             );
 
-        app.sub_app_mut(RenderApp)
-            .add_systems(ExtractSchedule, extract_multi_billboards);
+        app.sub_app_mut(RenderApp).add_systems(
+            ExtractSchedule,
+            (extract_multi_billboards, extract_view_render_layers),
+        );
     }
 }
 
@@ -44,4 +49,5 @@ pub struct MultiBillboardBundle {
     pub visibility: Visibility,
     pub inherited_visibility: InheritedVisibility,
     pub view_visibility: ViewVisibility,
+    pub render_layers: RenderLayers,
 }
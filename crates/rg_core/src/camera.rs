@@ -1,3 +1,5 @@
+#![allow(clippy::type_complexity)]
+
 use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
 use bevy::render::camera::RenderTarget;
@@ -86,21 +88,56 @@ impl Default for CameraController {
     }
 }
 
+/// Insets of the blit region, as fractions of the window size.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct CameraLetterbox {
+    pub top: f32,
+    pub bottom: f32,
+    pub left: f32,
+    pub right: f32,
+}
+
+impl CameraLetterbox {
+    /// Returns the top-left offset and the size of the visible region.
+    pub fn apply(&self, size: Vec2) -> (Vec2, Vec2) {
+        let offset = Vec2::new(self.left, self.top) * size;
+        let scale = Vec2::new(1.0 - self.left - self.right, 1.0 - self.top - self.bottom);
+        (offset, scale.max(Vec2::ZERO) * size)
+    }
+}
+
 #[derive(Debug, Component)]
 pub struct BlitTarget {
     image: Handle<Image>,
     sprite: Entity,
 }
 
+impl BlitTarget {
+    pub fn image(&self) -> &Handle<Image> {
+        &self.image
+    }
+}
+
+/// Dither offset of a single camera. Materials only hold one offset, so
+/// `GlobalDitherOffset` follows the camera with the lowest order.
+#[derive(Debug, Default, Clone, Copy, Component)]
+pub struct CameraDitherOffset(pub UVec2);
+
 fn create_blit_target(
     mut commands: Commands,
     q_controller: Query<Entity, (With<CameraController>, Without<BlitTarget>)>,
     mut images: ResMut<Assets<Image>>,
 ) {
-    let Ok(controller) = q_controller.get_single() else {
-        return;
-    };
+    for controller in &q_controller {
+        create_controller_blit_target(&mut commands, &mut images, controller);
+    }
+}
 
+fn create_controller_blit_target(
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    controller: Entity,
+) {
     let image = images.add(Image {
         texture_descriptor: TextureDescriptor {
             label: Some("camera blit target"),
@@ -135,14 +172,16 @@ fn create_blit_target(
 
     commands
         .entity(controller)
-        .insert(BlitTarget { image, sprite });
+        .insert((BlitTarget { image, sprite }, CameraDitherOffset::default()));
 }
 
 fn update_transform(mut q_controller: Query<&mut CameraController>, time: Res<Time>) {
-    let Ok(mut controller) = q_controller.get_single_mut() else {
-        return;
-    };
+    for mut controller in &mut q_controller {
+        update_controller_transform(&mut controller, &time);
+    }
+}
 
+fn update_controller_transform(controller: &mut CameraController, time: &Time) {
     let dist = controller
         .translation
         .distance_squared(controller.target_translation);
@@ -184,6 +223,8 @@ fn update_camera(
         &mut Projection,
         &mut Camera,
         &BlitTarget,
+        &mut CameraDitherOffset,
+        Option<&CameraLetterbox>,
     )>,
     mut q_sprite: Query<&mut Transform, Without<CameraController>>,
     mut dither_offset: ResMut<GlobalDitherOffset>,
@@ -195,80 +236,135 @@ fn update_camera(
         return;
     };
 
-    let Ok((controller, mut camera_transform, mut camera_projection, mut camera, blit_target)) =
-        q_controller.get_single_mut()
-    else {
-        return;
-    };
+    let mut primary_order = None;
+
+    for (
+        controller,
+        mut camera_transform,
+        mut camera_projection,
+        mut camera,
+        blit_target,
+        mut camera_dither_offset,
+        letterbox,
+    ) in &mut q_controller
+    {
+        let letterbox = letterbox.copied().unwrap_or_default();
+
+        let camera_rot_inv = controller.rotation.inverse();
+        let camera_scale = controller.camera_scale / controller.zoom;
+        let camera_distance = controller.camera_distance / controller.zoom;
+        let pixel_scale = game_scale.pixels as f32;
+
+        let basis = Vec3::new(
+            1.0,
+            controller.camera_pitch.sin().abs(),
+            controller.camera_pitch.cos().abs(),
+        ) / camera_scale;
+
+        let origin = camera_rot_inv * (world_origin.0.as_vec2() * CHUNK_SIZE).extend(0.0);
+        let pos = origin + camera_rot_inv * controller.translation;
+        let snapped_pos = (pos * basis).round() / basis;
+        let offset = snapped_pos - pos;
+
+        let dither = ((pos * basis % 4.0).round()).as_ivec3();
+        camera_dither_offset.0 = UVec2::new(
+            dither.x.rem_euclid(4) as u32,
+            (-dither.y - dither.z).rem_euclid(4) as u32,
+        );
 
-    let camera_rot_inv = controller.rotation.inverse();
-    let camera_scale = controller.camera_scale / controller.zoom;
-    let camera_distance = controller.camera_distance / controller.zoom;
-    let pixel_scale = game_scale.pixels as f32;
-
-    let basis = Vec3::new(
-        1.0,
-        controller.camera_pitch.sin().abs(),
-        controller.camera_pitch.cos().abs(),
-    ) / camera_scale;
-
-    let origin = camera_rot_inv * (world_origin.0.as_vec2() * CHUNK_SIZE).extend(0.0);
-    let pos = origin + camera_rot_inv * controller.translation;
-    let snapped_pos = (pos * basis).round() / basis;
-    let offset = snapped_pos - pos;
-
-    let dither = ((pos * basis % 4.0).round()).as_ivec3();
-    dither_offset.0 = UVec2::new(
-        dither.x.rem_euclid(4) as u32,
-        (-dither.y - dither.z).rem_euclid(4) as u32,
-    );
+        if primary_order.map_or(true, |order| camera.order < order) {
+            primary_order = Some(camera.order);
+            dither_offset.0 = camera_dither_offset.0;
+        }
 
-    camera_transform.rotation =
-        controller.rotation * Quat::from_rotation_x(-controller.camera_pitch);
-    camera_transform.translation = controller.translation
-        + controller.rotation * offset
-        + camera_transform.rotation * Vec3::Y * -camera_distance;
+        camera_transform.rotation =
+            controller.rotation * Quat::from_rotation_x(-controller.camera_pitch);
+        camera_transform.translation = controller.translation
+            + controller.rotation * offset
+            + camera_transform.rotation * Vec3::Y * -camera_distance;
 
-    camera_transform.rotate_local_x(90f32.to_radians());
+        camera_transform.rotate_local_x(90f32.to_radians());
 
-    *camera_projection = Projection::Orthographic(OrthographicProjection {
-        near: controller.camera_near,
-        far: controller.camera_far / controller.zoom,
-        scale: camera_scale,
-        ..default()
-    });
+        *camera_projection = Projection::Orthographic(OrthographicProjection {
+            near: controller.camera_near,
+            far: controller.camera_far / controller.zoom,
+            scale: camera_scale,
+            ..default()
+        });
+
+        camera.target = RenderTarget::Image(blit_target.image.clone());
+
+        let window_size = Vec2::new(
+            window.physical_width() as f32,
+            window.physical_height() as f32,
+        );
+
+        let shift = Vec2::new(
+            offset.x * basis.x - 0.5,
+            offset.z * basis.z + offset.y * basis.y + 1.0,
+        );
+
+        let (extent, transform) = blit_layout(
+            window_size,
+            window.scale_factor() as f32,
+            pixel_scale,
+            &letterbox,
+            shift,
+        );
+
+        let Some(image) = images.get_mut(&blit_target.image) else {
+            continue;
+        };
 
-    camera.target = RenderTarget::Image(blit_target.image.clone());
+        image.resize(extent);
 
-    let width = (window.physical_width() as f32 / pixel_scale).ceil() as u32 + 2;
-    let height = (window.physical_height() as f32 / pixel_scale).ceil() as u32 + 2;
+        let Ok(mut sprite_transform) = q_sprite.get_mut(blit_target.sprite) else {
+            continue;
+        };
+
+        *sprite_transform = transform;
+    }
+}
+
+/// Size of the blit target and the transform of its sprite. `window_size` is
+/// in physical pixels, `shift` is the sub-pixel snapping offset in texels.
+fn blit_layout(
+    window_size: Vec2,
+    scale_factor: f32,
+    pixel_scale: f32,
+    letterbox: &CameraLetterbox,
+    shift: Vec2,
+) -> (Extent3d, Transform) {
+    let (inset, physical_size) = letterbox.apply(window_size);
+
+    // The sprite is shifted by up to two texels for sub-pixel snapping, and
+    // then rounded to whole physical pixels, so one more texel of padding
+    // keeps the window covered at fractional scale factors.
+    let width = (physical_size.x / pixel_scale).ceil() as u32 + 3;
+    let height = (physical_size.y / pixel_scale).ceil() as u32 + 3;
     let extent = Extent3d {
         width,
         height,
         ..default()
     };
 
-    let scale_factor = window.scale_factor() as f32;
-
-    let Some(image) = images.get_mut(&blit_target.image) else {
-        return;
-    };
-
-    image.resize(extent);
+    // position in physical pixels, relative to the window's top left corner
+    let top_left = Vec2::new(
+        inset.x.round() + (shift.x * pixel_scale).round(),
+        inset.y.round() - (shift.y * pixel_scale).round(),
+    );
 
-    let Ok(mut sprite_transform) = q_sprite.get_mut(blit_target.sprite) else {
-        return;
+    let transform = Transform {
+        translation: Vec3::new(
+            (top_left.x - window_size.x / 2.0) / scale_factor,
+            (window_size.y / 2.0 - top_left.y) / scale_factor,
+            0.0,
+        ),
+        scale: Vec3::splat(pixel_scale / scale_factor),
+        ..default()
     };
 
-    let sprite_scale = pixel_scale / scale_factor;
-
-    sprite_transform.scale = Vec3::splat(sprite_scale);
-    sprite_transform.translation = Vec3::new(
-        -window.width() / 2.0 + ((offset.x * basis.x - 0.5) * sprite_scale).round(),
-        window.height() / 2.0
-            + ((offset.z * basis.z + offset.y * basis.y + 1.0) * sprite_scale).round(),
-        0.0,
-    );
+    (extent, transform)
 }
 
 fn handle_input(
@@ -276,10 +372,24 @@ fn handle_input(
     keyboard_input: Res<Input<KeyCode>>,
     mut scroll_events: EventReader<MouseWheel>,
 ) {
-    let Ok(mut camera) = q_camera.get_single_mut() else {
-        return;
-    };
+    let scroll_delta = scroll_events
+        .read()
+        .map(|scroll_event| match scroll_event.unit {
+            MouseScrollUnit::Line => scroll_event.y,
+            MouseScrollUnit::Pixel => scroll_event.y / 16.0,
+        })
+        .collect::<Vec<_>>();
+
+    for mut camera in &mut q_camera {
+        handle_controller_input(&mut camera, &keyboard_input, &scroll_delta);
+    }
+}
 
+fn handle_controller_input(
+    camera: &mut CameraController,
+    keyboard_input: &Input<KeyCode>,
+    scroll_delta: &[f32],
+) {
     if keyboard_input.just_pressed(KeyCode::Q) {
         camera.target_rotation *= Quat::from_rotation_z(45f32.to_radians());
     }
@@ -288,12 +398,7 @@ fn handle_input(
         camera.target_rotation *= Quat::from_rotation_z(-45f32.to_radians());
     }
 
-    for scroll_event in scroll_events.read() {
-        let delta = match scroll_event.unit {
-            MouseScrollUnit::Line => scroll_event.y,
-            MouseScrollUnit::Pixel => scroll_event.y / 16.0,
-        };
-
+    for &delta in scroll_delta {
         let sensitivity = 0.1;
         let min_zoom = 0.125;
         let max_zoom = 2.0;
@@ -316,13 +421,11 @@ fn handle_updated_origin(
     mut ev_origin_changed: EventReader<WorldOriginChanged>,
     mut q_camera: Query<&mut CameraController>,
 ) {
-    let Ok(mut camera) = q_camera.get_single_mut() else {
-        return;
-    };
-
     for event in ev_origin_changed.read() {
-        camera.translation += event.translation;
-        camera.target_translation += event.translation;
+        for mut camera in &mut q_camera {
+            camera.translation += event.translation;
+            camera.target_translation += event.translation;
+        }
     }
 }
 
@@ -330,3 +433,112 @@ fn handle_updated_origin(
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a * (1.0 - t) + b * t
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn letterbox_extent() {
+        let size = Vec2::new(1280.0, 720.0);
+
+        let none = CameraLetterbox::default();
+        assert_eq!(none.apply(size), (Vec2::ZERO, size));
+
+        let letterbox = CameraLetterbox {
+            top: 0.1,
+            bottom: 0.2,
+            left: 0.25,
+            right: 0.0,
+        };
+        let (offset, extent) = letterbox.apply(size);
+        assert_eq!(offset, Vec2::new(320.0, 72.0));
+        assert!((extent - Vec2::new(960.0, 504.0)).abs().max_element() < 1e-3);
+
+        let overlapping = CameraLetterbox {
+            left: 0.7,
+            right: 0.6,
+            ..default()
+        };
+        assert_eq!(overlapping.apply(size).1, Vec2::new(0.0, 720.0));
+    }
+
+    /// Checks that the blit sprite covers the visible part of the window.
+    fn assert_blit_covers(
+        window_size: Vec2,
+        scale_factor: f32,
+        letterbox: &CameraLetterbox,
+        shift: Vec2,
+    ) {
+        let pixel_scale = 3.0;
+        let (extent, transform) =
+            blit_layout(window_size, scale_factor, pixel_scale, letterbox, shift);
+
+        // back to physical pixels from the window's top left corner
+        let min = Vec2::new(
+            transform.translation.x * scale_factor + window_size.x / 2.0,
+            window_size.y / 2.0 - transform.translation.y * scale_factor,
+        );
+        let size =
+            Vec2::new(extent.width as f32, extent.height as f32) * transform.scale.x * scale_factor;
+
+        let (inset, visible) = letterbox.apply(window_size);
+        let visible_min = inset.round();
+        let visible_max = visible_min + visible;
+
+        assert!(min.cmple(visible_min + 1e-3).all(), "{min} {visible_min}");
+        assert!(
+            (min + size).cmpge(visible_max - 1e-3).all(),
+            "{size} {visible}"
+        );
+    }
+
+    #[test]
+    fn blit_covers_window() {
+        let letterboxes = [
+            CameraLetterbox::default(),
+            CameraLetterbox {
+                top: 0.1,
+                bottom: 0.05,
+                left: 0.3,
+                right: 0.0,
+            },
+        ];
+
+        // snapping shifts by -1..0 texels horizontally and 0..2 vertically
+        let shifts = [
+            Vec2::new(-1.0, 0.0),
+            Vec2::new(0.0, 2.0),
+            Vec2::new(-0.5, 1.0),
+        ];
+
+        for window_size in [Vec2::new(1280.0, 720.0), Vec2::new(1921.0, 1079.0)] {
+            for scale_factor in [1.0, 1.25, 1.5, 2.0] {
+                for letterbox in &letterboxes {
+                    for shift in shifts {
+                        assert_blit_covers(window_size, scale_factor, letterbox, shift);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn controllers_get_distinct_blit_targets() {
+        let mut world = World::new();
+        world.init_resource::<Assets<Image>>();
+        world.spawn(CameraController::default());
+        world.spawn(CameraController::default());
+
+        world.run_system_once(create_blit_target);
+
+        let mut q_target = world.query::<&BlitTarget>();
+        let targets = q_target.iter(&world).collect::<Vec<_>>();
+        assert_eq!(targets.len(), 2);
+        assert_ne!(targets[0].image, targets[1].image);
+        assert_ne!(targets[0].sprite, targets[1].sprite);
+        assert_eq!(world.resource::<Assets<Image>>().len(), 2);
+    }
+}
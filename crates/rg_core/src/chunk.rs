@@ -16,6 +16,7 @@ pub struct ChunkPlugin;
 impl Plugin for ChunkPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<WorldOriginChanged>()
+            .init_resource::<ChunkDimensions>()
             .init_resource::<Chunks>()
             .init_resource::<ChunkSpawnCenter>()
             .init_resource::<ChunkSpawnRadius>()
@@ -36,6 +37,33 @@ impl Plugin for ChunkPlugin {
     }
 }
 
+/// Runtime counterpart of `CHUNK_TILES` and `TILE_SIZE`, for code that
+/// shouldn't bake the chunk layout in at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct ChunkDimensions {
+    pub tiles: u32,
+    pub tile_size: f32,
+}
+
+impl ChunkDimensions {
+    pub fn chunk_size(&self) -> f32 {
+        self.tiles as f32 * self.tile_size
+    }
+
+    pub fn tile_pos_to_world(&self, origin: IVec2, chunk: IVec2, tile: IVec2) -> Vec2 {
+        (chunk - origin).as_vec2() * self.chunk_size() + tile.as_vec2() * self.tile_size
+    }
+}
+
+impl Default for ChunkDimensions {
+    fn default() -> Self {
+        Self {
+            tiles: CHUNK_TILES,
+            tile_size: TILE_SIZE,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default, Resource)]
 pub struct WorldOrigin(pub IVec2);
 
@@ -131,9 +159,33 @@ fn spawn_chunks(
     let radius = radius.0;
     let origin = origin.0;
 
+    for chunk_pos in chunks_in_radius(origin, center, radius) {
+        if chunks.contains(chunk_pos) {
+            continue;
+        }
+
+        let new_chunk = commands
+            .spawn((
+                Name::new("Chunk"),
+                Chunk,
+                ChunkPos(chunk_pos),
+                Transform::from_translation(chunk_pos_to_world(origin, chunk_pos).extend(0.0)),
+                GlobalTransform::default(),
+                VisibilityBundle::default(),
+                FloatingOrigin,
+            ))
+            .id();
+
+        chunks.insert(chunk_pos, new_chunk);
+    }
+}
+
+fn chunks_in_radius(origin: IVec2, center: Vec2, radius: f32) -> Vec<IVec2> {
     let chunk_center = origin + (center / CHUNK_SIZE).round().as_ivec2();
     let chunk_dist = (Vec2::new(radius, radius) / CHUNK_SIZE).ceil().as_ivec2();
 
+    let mut chunks = Vec::new();
+
     for sx in -chunk_dist.x..=chunk_dist.x {
         for sy in -chunk_dist.y..=chunk_dist.y {
             let chunk_pos = chunk_center + IVec2::new(sx, sy);
@@ -143,25 +195,12 @@ fn spawn_chunks(
                 continue;
             }
 
-            if chunks.contains(chunk_pos) {
-                continue;
-            }
-
-            let new_chunk = commands
-                .spawn((
-                    Name::new("Chunk"),
-                    Chunk,
-                    ChunkPos(chunk_pos),
-                    Transform::from_translation(chunk_pos_to_world(origin, chunk_pos).extend(0.0)),
-                    GlobalTransform::default(),
-                    VisibilityBundle::default(),
-                    FloatingOrigin,
-                ))
-                .id();
-
-            chunks.insert(chunk_pos, new_chunk);
+            chunks.push(chunk_pos);
         }
     }
+
+    chunks.sort_unstable_by_key(|chunk_pos| (chunk_pos.y, chunk_pos.x));
+    chunks
 }
 
 fn despawn_chunks(
@@ -210,3 +249,19 @@ fn update_origin(
 
     ev_origin_changed.send(WorldOriginChanged { translation });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_in_radius_sorted_row_major() {
+        let origin = IVec2::new(3, -2);
+        let chunks = chunks_in_radius(origin, Vec2::splat(8.0), 2.5 * CHUNK_SIZE);
+
+        assert!(chunks.contains(&origin));
+        assert!(chunks
+            .windows(2)
+            .all(|w| (w[0].y, w[0].x) < (w[1].y, w[1].x)));
+    }
+}
@@ -0,0 +1,114 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use bevy::prelude::*;
+
+use super::Grid;
+
+/// Grid of `f32` which can be accumulated into from multiple threads.
+///
+/// The summation order is unspecified, so results may differ in the last bits
+/// between runs.
+pub struct AtomicGrid {
+    inner: Grid<AtomicU32>,
+}
+
+impl AtomicGrid {
+    pub fn new(size: UVec2) -> AtomicGrid {
+        AtomicGrid {
+            inner: Grid::from_fn(size, |_| AtomicU32::new(0.0f32.to_bits())),
+        }
+    }
+
+    pub fn from_grid(grid: &Grid<f32>) -> AtomicGrid {
+        AtomicGrid {
+            inner: grid.map(|_, v| AtomicU32::new(v.to_bits())),
+        }
+    }
+
+    pub fn origin(&self) -> IVec2 {
+        self.inner.origin()
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.inner.size()
+    }
+
+    pub fn contains_cell(&self, cell: IVec2) -> bool {
+        self.inner.contains_cell(cell)
+    }
+
+    pub fn get(&self, cell: IVec2) -> Option<f32> {
+        let value = self.inner.get(cell)?;
+        Some(f32::from_bits(value.load(Ordering::Relaxed)))
+    }
+
+    /// Adds `value` to the cell. Returns false if the cell is out of bounds.
+    pub fn add(&self, cell: IVec2, value: f32) -> bool {
+        let Some(atomic) = self.inner.get(cell) else {
+            return false;
+        };
+
+        let mut current = atomic.load(Ordering::Relaxed);
+        loop {
+            let new = (f32::from_bits(current) + value).to_bits();
+            match atomic.compare_exchange_weak(current, new, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return true,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    pub fn into_grid(self) -> Grid<f32> {
+        let origin = self.inner.origin();
+        let size = self.inner.size();
+        let data = self
+            .inner
+            .data
+            .into_vec()
+            .into_iter()
+            .map(|v| f32::from_bits(v.into_inner()))
+            .collect::<Vec<_>>();
+        Grid::from_data(size, data).with_origin(origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn parallel_sum_matches_serial() {
+        let size = UVec2::new(16, 9);
+        let contribution = |i: u32| {
+            let cell = IVec2::new((i * 7 % size.x) as i32, (i * 13 % size.y) as i32);
+            // multiples of 0.25 are summed exactly in any order
+            (cell, (i % 5) as f32 * 0.25)
+        };
+
+        let mut serial = Grid::new(size, 0.0);
+        for i in 0..10_000 {
+            let (cell, value) = contribution(i);
+            serial[cell] += value;
+        }
+
+        let parallel = AtomicGrid::new(size);
+        (0..10_000).into_par_iter().for_each(|i| {
+            let (cell, value) = contribution(i);
+            assert!(parallel.add(cell, value));
+        });
+        let parallel = parallel.into_grid();
+
+        for (cell, &value) in serial.entries() {
+            assert_eq!(parallel[cell], value, "{cell}");
+        }
+    }
+
+    #[test]
+    fn add_out_of_bounds() {
+        let grid = AtomicGrid::new(UVec2::splat(4));
+        assert!(!grid.add(IVec2::new(4, 0), 1.0));
+        assert!(!grid.add(IVec2::new(-1, 2), 1.0));
+    }
+}
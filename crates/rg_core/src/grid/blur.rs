@@ -10,32 +10,17 @@ const MAX_KERNEL_SIZE: usize = 63;
 const MIN_SIGMA: f32 = 0.1;
 
 impl Grid<f32> {
+    /// Box blur, see `gaussian_blur` for a smooth falloff.
     pub fn blur(&mut self, kernel_size: i32) {
         let _scope = info_span!("blur").entered();
 
         let mut temp = self.clone();
-
-        temp.par_entries_mut().for_each(|(cell, value)| {
-            let mut sum = 0.0;
-
-            for sx in -kernel_size..=kernel_size {
-                sum += self.clamped_get(cell + IVec2::new(sx, 0));
-            }
-
-            *value = sum / (2 * kernel_size + 1) as f32;
-        });
-
-        self.par_entries_mut().for_each(|(cell, value)| {
-            let mut sum = 0.0;
-
-            for sy in -kernel_size..=kernel_size {
-                sum += temp.clamped_get(cell + IVec2::new(0, sy));
-            }
-
-            *value = sum / (2 * kernel_size + 1) as f32;
-        });
+        box_blur_rows(self, &mut temp, kernel_size);
+        box_blur_columns(&temp, self, kernel_size);
     }
 
+    /// Separable Gaussian blur with a normalized kernel spanning about 2.5
+    /// sigma each way. Edges are clamped.
     pub fn gaussian_blur(&mut self, sigma: f32) {
         let _scope = info_span!("gaussian_blur").entered();
 
@@ -73,6 +58,20 @@ impl Grid<f32> {
         });
     }
 
+    /// Unsharp mask against a 3x3 box blur. Flat regions are unchanged.
+    pub fn sharpen(&mut self, amount: f32) {
+        let _scope = info_span!("sharpen").entered();
+
+        let mut blurred = self.clone();
+        blurred.blur(1);
+
+        self.par_values_mut()
+            .zip(blurred.par_values())
+            .for_each(|(value, blurred)| {
+                *value += amount * (*value - blurred);
+            });
+    }
+
     pub fn variable_gaussian_blur(
         &mut self,
         sigma_map: &Grid<f32>,
@@ -134,6 +133,46 @@ impl Grid<f32> {
     }
 }
 
+fn box_blur_rows(src: &Grid<f32>, dst: &mut Grid<f32>, kernel_size: i32) {
+    let max_x = src.size().x as i32 - 1;
+
+    dst.par_rows_mut()
+        .zip(src.par_rows())
+        .for_each(|(dst_row, src_row)| {
+            for (x, value) in dst_row.iter_mut().enumerate() {
+                let mut sum = 0.0;
+
+                for sx in -kernel_size..=kernel_size {
+                    sum += src_row[(x as i32 + sx).clamp(0, max_x) as usize];
+                }
+
+                *value = sum / (2 * kernel_size + 1) as f32;
+            }
+        });
+}
+
+/// Vertical pass, sums whole source rows into each destination row so that
+/// memory is still read row by row.
+fn box_blur_columns(src: &Grid<f32>, dst: &mut Grid<f32>, kernel_size: i32) {
+    let min_y = src.origin().y;
+    let max_y = min_y + src.size().y as i32 - 1;
+
+    dst.par_rows_mut().enumerate().for_each(|(y, dst_row)| {
+        dst_row.fill(0.0);
+
+        for sy in -kernel_size..=kernel_size {
+            let src_row = src.row((min_y + y as i32 + sy).clamp(min_y, max_y));
+            for (value, src) in dst_row.iter_mut().zip(src_row) {
+                *value += src;
+            }
+        }
+
+        for value in dst_row {
+            *value /= (2 * kernel_size + 1) as f32;
+        }
+    });
+}
+
 fn gaussian_kernel_size(sigma: f32) -> usize {
     let v = (2.0 * (sigma * 2.5).ceil() + 1.0) as usize;
     v.max(3).min(MAX_KERNEL_SIZE)
@@ -189,3 +228,94 @@ fn fast_exp(x: f32) -> f32 {
     let approx = (C0 * frac + C1) * frac + C2;
     f32::from_bits(approx.to_bits().wrapping_add(floor as u32))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sum(grid: &Grid<f32>) -> f32 {
+        grid.values().sum()
+    }
+
+    #[test]
+    fn gaussian_blur_preserves_sum() {
+        let mut grid = Grid::new(UVec2::splat(64), 0.0);
+        grid[IVec2::new(32, 30)] = 10.0;
+        grid[IVec2::new(28, 35)] = 4.0;
+        let original = sum(&grid);
+
+        grid.gaussian_blur(2.5);
+
+        assert!((sum(&grid) - original).abs() < 1e-3);
+        assert!(grid[IVec2::new(32, 30)] < 10.0);
+        assert!(grid[IVec2::new(33, 30)] > 0.0);
+    }
+
+    #[test]
+    fn gaussian_blur_keeps_constant_grid() {
+        let mut grid = Grid::new(UVec2::new(20, 12), 3.0);
+        grid.gaussian_blur(4.0);
+
+        for &value in grid.values() {
+            assert!((value - 3.0).abs() < 1e-4);
+        }
+    }
+
+    /// The blur as it was before both passes were made row-major.
+    fn reference_blur(grid: &Grid<f32>, kernel_size: i32) -> Grid<f32> {
+        let mut temp = grid.clone();
+        temp.entries_mut().for_each(|(cell, value)| {
+            let mut sum = 0.0;
+            for sx in -kernel_size..=kernel_size {
+                sum += grid.clamped_get(cell + IVec2::new(sx, 0));
+            }
+            *value = sum / (2 * kernel_size + 1) as f32;
+        });
+
+        let mut res = grid.clone();
+        res.entries_mut().for_each(|(cell, value)| {
+            let mut sum = 0.0;
+            for sy in -kernel_size..=kernel_size {
+                sum += temp.clamped_get(cell + IVec2::new(0, sy));
+            }
+            *value = sum / (2 * kernel_size + 1) as f32;
+        });
+
+        res
+    }
+
+    #[test]
+    fn blur_matches_reference() {
+        let grid = Grid::from_fn_with_origin(UVec2::new(23, 17), IVec2::new(-5, 3), |cell| {
+            ((cell.x * 7 + cell.y * 13) % 11) as f32
+        });
+
+        for kernel_size in [1, 2, 5] {
+            let expected = reference_blur(&grid, kernel_size);
+            let mut blurred = grid.clone();
+            blurred.blur(kernel_size);
+
+            assert_eq!(blurred.origin(), grid.origin());
+            for (cell, &value) in blurred.entries() {
+                assert!((value - expected[cell]).abs() < 1e-5, "{cell}");
+            }
+        }
+    }
+
+    #[test]
+    fn sharpen_steepens_edge() {
+        let mut grid = Grid::from_fn(UVec2::new(10, 6), |cell| f32::from(cell.x >= 5));
+        let original = grid.clone();
+        grid.sharpen(1.0);
+
+        for y in 0..6 {
+            assert!(grid[IVec2::new(4, y)] < 0.0);
+            assert!(grid[IVec2::new(5, y)] > 1.0);
+
+            for x in (0..3).chain(7..10) {
+                let cell = IVec2::new(x, y);
+                assert_eq!(grid[cell], original[cell], "{cell}");
+            }
+        }
+    }
+}
@@ -5,7 +5,11 @@ use super::Grid;
 
 impl Grid<bool> {
     pub fn compute_edt(&self, settings: EdtSettings) -> Grid<f32> {
-        let _scope = info_span!("compute_edt").entered();
+        self.compute_distance(DistanceMetric::Euclidean, settings)
+    }
+
+    pub fn compute_distance(&self, metric: DistanceMetric, settings: EdtSettings) -> Grid<f32> {
+        let _scope = info_span!("compute_distance").entered();
 
         let mut tmp_grid = Grid::from_fn(self.size + settings.padding * 2, |cell| {
             let orig_cell = cell - (settings.padding as i32);
@@ -16,12 +20,17 @@ impl Grid<bool> {
             }
         });
 
-        dt2d_float(&mut tmp_grid);
+        match metric {
+            DistanceMetric::Euclidean => {
+                dt2d_float(&mut tmp_grid);
+                tmp_grid.par_values_mut().for_each(|v| *v = v.sqrt());
+            }
+            DistanceMetric::Manhattan => chamfer(&mut tmp_grid, &MANHATTAN_MASK),
+            DistanceMetric::Chebyshev => chamfer(&mut tmp_grid, &CHEBYSHEV_MASK),
+        }
 
         let mut res_grid = Grid::from_fn(self.size, |cell| {
-            tmp_grid
-                .clamped_get(cell + (settings.padding as i32))
-                .sqrt()
+            *tmp_grid.clamped_get(cell + (settings.padding as i32))
         });
 
         if settings.normalize {
@@ -32,13 +41,58 @@ impl Grid<bool> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct EdtSettings {
     pub invert: bool,
     pub normalize: bool,
     pub padding: u32,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DistanceMetric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+}
+
+/// Neighbors preceding a cell in scan order. Negated for the backward pass.
+const MANHATTAN_MASK: [IVec2; 2] = [IVec2::new(0, -1), IVec2::new(-1, 0)];
+
+const CHEBYSHEV_MASK: [IVec2; 4] = [
+    IVec2::new(0, -1),
+    IVec2::new(-1, 0),
+    IVec2::new(-1, -1),
+    IVec2::new(1, -1),
+];
+
+/// Two-pass chamfer transform with unit step costs.
+fn chamfer(grid: &mut Grid<f32>, mask: &[IVec2]) {
+    let size = grid.size.as_ivec2();
+
+    let relax = |grid: &mut Grid<f32>, cell: IVec2, dir: IVec2| {
+        if let Some(&neighbor) = grid.get(cell + dir) {
+            grid[cell] = grid[cell].min(neighbor + 1.0);
+        }
+    };
+
+    for y in 0..size.y {
+        for x in 0..size.x {
+            for &dir in mask {
+                relax(grid, IVec2::new(x, y), dir);
+            }
+        }
+    }
+
+    for y in (0..size.y).rev() {
+        for x in (0..size.x).rev() {
+            for &dir in mask {
+                relax(grid, IVec2::new(x, y), -dir);
+            }
+        }
+    }
+}
+
 fn dt1d_float(d: &mut [f32], v: &mut [i32], z: &mut [f32], f: &[f32]) {
     d.fill(0.0);
     v.fill(0);
@@ -102,3 +156,23 @@ fn dt2d_float(grid: &mut Grid<f32>) {
         grid.transpose_in_place();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chamfer_distances_from_single_cell() {
+        let seed = IVec2::new(3, 2);
+        let grid = Grid::from_fn(UVec2::new(7, 5), |cell| cell != seed);
+
+        let manhattan = grid.compute_distance(DistanceMetric::Manhattan, EdtSettings::default());
+        let chebyshev = grid.compute_distance(DistanceMetric::Chebyshev, EdtSettings::default());
+
+        for cell in grid.cells() {
+            let delta = (cell - seed).abs();
+            assert_eq!(manhattan[cell], (delta.x + delta.y) as f32, "{cell}");
+            assert_eq!(chebyshev[cell], delta.max_element() as f32, "{cell}");
+        }
+    }
+}
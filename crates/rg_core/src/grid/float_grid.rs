@@ -1,11 +1,31 @@
 use std::path::Path;
 
+use anyhow::Context;
 use bevy::prelude::*;
-use bytemuck::cast_slice;
+use rand::Rng;
+use rayon::prelude::*;
 
 use super::Grid;
 use crate::noise::Noise;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleMode {
+    /// Returns the first non-NaN corner if any corner is NaN.
+    SkipNan,
+    /// Interpolates between the non-NaN corners only.
+    AverageNonNan,
+    PropagateNan,
+    TreatNanAs(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StampOp {
+    Add,
+    Max,
+    Min,
+    Replace,
+}
+
 impl Grid<f32> {
     pub fn add_noise<N: Noise<1> + Sync>(&mut self, noise: &N) {
         let _scope = info_span!("add_noise").entered();
@@ -16,6 +36,10 @@ impl Grid<f32> {
     }
 
     pub fn sample(&self, pos: Vec2) -> f32 {
+        self.sample_with(pos, SampleMode::SkipNan)
+    }
+
+    pub fn sample_with(&self, pos: Vec2, mode: SampleMode) -> f32 {
         let ipos = pos.as_ivec2();
         let fpos = pos - ipos.as_vec2();
 
@@ -25,19 +49,229 @@ impl Grid<f32> {
         let br = *self.clamped_get(ipos + IVec2::new(1, 1));
 
         let vals = [tl, tr, bl, br];
-        if vals.iter().any(|v| v.is_nan()) {
-            return *vals.iter().find(|v| !v.is_nan()).unwrap_or(&f32::NAN);
+        if !vals.iter().any(|v| v.is_nan()) {
+            return lerp(lerp(tl, tr, fpos.x), lerp(bl, br, fpos.x), fpos.y);
+        }
+
+        match mode {
+            SampleMode::SkipNan => *vals.iter().find(|v| !v.is_nan()).unwrap_or(&f32::NAN),
+            SampleMode::AverageNonNan => {
+                let weights = [
+                    (1.0 - fpos.x) * (1.0 - fpos.y),
+                    fpos.x * (1.0 - fpos.y),
+                    (1.0 - fpos.x) * fpos.y,
+                    fpos.x * fpos.y,
+                ];
+
+                let mut sum = 0.0;
+                let mut weight_sum = 0.0;
+
+                for (&v, &w) in vals.iter().zip(&weights) {
+                    if !v.is_nan() {
+                        sum += v * w;
+                        weight_sum += w;
+                    }
+                }
+
+                if weight_sum > 0.0 {
+                    sum / weight_sum
+                } else {
+                    *vals.iter().find(|v| !v.is_nan()).unwrap_or(&f32::NAN)
+                }
+            }
+            SampleMode::PropagateNan => f32::NAN,
+            SampleMode::TreatNanAs(fill) => {
+                let [tl, tr, bl, br] = vals.map(|v| if v.is_nan() { fill } else { v });
+                lerp(lerp(tl, tr, fpos.x), lerp(bl, br, fpos.x), fpos.y)
+            }
         }
+    }
+
+    pub fn sample_reflect(&self, pos: Vec2) -> f32 {
+        let ipos = pos.floor().as_ivec2();
+        let fpos = pos - ipos.as_vec2();
+
+        let tl = *self.reflected_get(ipos + IVec2::new(0, 0));
+        let tr = *self.reflected_get(ipos + IVec2::new(1, 0));
+        let bl = *self.reflected_get(ipos + IVec2::new(0, 1));
+        let br = *self.reflected_get(ipos + IVec2::new(1, 1));
 
         lerp(lerp(tl, tr, fpos.x), lerp(bl, br, fpos.x), fpos.y)
     }
 
+    /// Catmull-Rom interpolation over the surrounding 4x4 cells. Falls back to
+    /// `sample` if any of them is NaN.
+    pub fn sample_bicubic(&self, pos: Vec2) -> f32 {
+        let ipos = pos.floor().as_ivec2();
+        let fpos = pos - ipos.as_vec2();
+
+        let mut rows = [0.0; 4];
+        for (y, row) in rows.iter_mut().enumerate() {
+            let values: [f32; 4] = std::array::from_fn(|x| {
+                *self.clamped_get(ipos + IVec2::new(x as i32 - 1, y as i32 - 1))
+            });
+
+            if values.iter().any(|v| v.is_nan()) {
+                return self.sample(pos);
+            }
+
+            *row = catmull_rom(values, fpos.x);
+        }
+
+        catmull_rom(rows, fpos.y)
+    }
+
     pub fn sample_grad(&self, pos: Vec2) -> Vec2 {
-        let l = self.sample(pos - Vec2::X);
-        let r = self.sample(pos + Vec2::X);
-        let t = self.sample(pos - Vec2::Y);
-        let b = self.sample(pos + Vec2::Y);
-        Vec2::new((r - l) * 0.5, (b - t) * 0.5)
+        self.sample_grad_bilinear(pos, 1.0)
+    }
+
+    pub fn sample_grad_bilinear(&self, pos: Vec2, h: f32) -> Vec2 {
+        let l = self.sample(pos - Vec2::X * h);
+        let r = self.sample(pos + Vec2::X * h);
+        let t = self.sample(pos - Vec2::Y * h);
+        let b = self.sample(pos + Vec2::Y * h);
+        Vec2::new(r - l, b - t) / (2.0 * h)
+    }
+
+    /// Central-difference gradient of every cell, clamped at the borders.
+    pub fn par_gradient(&self) -> Grid<Vec2> {
+        let _scope = info_span!("par_gradient").entered();
+
+        Grid::par_from_fn_with_origin(self.size, self.origin, |cell| {
+            let l = *self.clamped_get(cell - IVec2::X);
+            let r = *self.clamped_get(cell + IVec2::X);
+            let t = *self.clamped_get(cell - IVec2::Y);
+            let b = *self.clamped_get(cell + IVec2::Y);
+            Vec2::new(r - l, b - t) / 2.0
+        })
+    }
+
+    pub fn sample_polyline(&self, points: &[Vec2], step: f32) -> Vec<f32> {
+        let mut samples = Vec::new();
+
+        if step <= 0.0 {
+            return samples;
+        }
+
+        if let [point] = points {
+            samples.push(self.sample(*point));
+            return samples;
+        }
+
+        let mut offset = 0.0;
+
+        for segment in points.windows(2) {
+            let start = segment[0];
+            let end = segment[1];
+            let len = start.distance(end);
+
+            while offset <= len {
+                let t = if len > 0.0 { offset / len } else { 0.0 };
+                samples.push(self.sample(start.lerp(end, t)));
+                offset += step;
+            }
+
+            offset -= len;
+        }
+
+        samples
+    }
+
+    /// Sets cells whose centers lie within the circle. With `soft`, edge cells
+    /// are blended towards `value` by their approximate coverage.
+    pub fn fill_circle(&mut self, center: Vec2, radius: f32, value: f32, soft: bool) {
+        let grid_min = self.origin;
+        let grid_max = self.origin + self.size.as_ivec2() - 1;
+        let min = (center - radius - 1.0).floor().as_ivec2().max(grid_min);
+        let max = (center + radius + 1.0).ceil().as_ivec2().min(grid_max);
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = IVec2::new(x, y);
+                let dist = (cell.as_vec2() + 0.5).distance(center);
+
+                let coverage = if soft {
+                    (radius - dist + 0.5).clamp(0.0, 1.0)
+                } else {
+                    f32::from(dist <= radius)
+                };
+
+                if coverage > 0.0 {
+                    let target = &mut self[cell];
+                    *target = lerp(*target, value, coverage);
+                }
+            }
+        }
+    }
+
+    /// Sets all cells in `min..=max`, clipped to the grid.
+    pub fn fill_rect(&mut self, min: IVec2, max: IVec2, value: f32) {
+        let min = min.max(self.origin);
+        let max = max.min(self.origin + self.size.as_ivec2() - 1);
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self[IVec2::new(x, y)] = value;
+            }
+        }
+    }
+
+    /// Draws `n` cells with probability proportional to their values. Negative
+    /// and NaN values are treated as zero. Returns nothing if all weights are
+    /// zero.
+    pub fn sample_cells<R: Rng>(&self, rng: &mut R, n: usize) -> Vec<IVec2> {
+        let mut total = 0.0;
+        let prefix_sums = self
+            .values()
+            .map(|&v| {
+                if v > 0.0 {
+                    total += v as f64;
+                }
+                total
+            })
+            .collect::<Vec<f64>>();
+
+        if total <= 0.0 {
+            return Vec::new();
+        }
+
+        let width = self.size.x as usize;
+
+        (0..n)
+            .map(|_| {
+                let target = rng.gen_range(0.0..total);
+                let index = prefix_sums.partition_point(|&sum| sum <= target);
+                self.origin + IVec2::new((index % width) as i32, (index / width) as i32)
+            })
+            .collect()
+    }
+
+    pub fn stamp(&mut self, center: Vec2, brush: &Grid<f32>, op: StampOp) {
+        let offset = (center - brush.size().as_vec2() * 0.5).round().as_ivec2() - brush.origin();
+
+        for (cell, &value) in brush.entries() {
+            let Some(target) = self.get_mut(cell + offset) else {
+                continue;
+            };
+
+            *target = match op {
+                StampOp::Add => *target + value,
+                StampOp::Max => target.max(value),
+                StampOp::Min => target.min(value),
+                StampOp::Replace => value,
+            };
+        }
+    }
+
+    pub fn lerp_with(&self, other: &Grid<f32>, weight: &Grid<f32>) -> Grid<f32> {
+        let _scope = info_span!("lerp_with").entered();
+
+        assert_eq!(self.size, other.size);
+        assert_eq!(self.origin, other.origin);
+        assert_eq!(self.size, weight.size);
+        assert_eq!(self.origin, weight.origin);
+
+        self.par_map(|cell, &a| lerp(a, other[cell], weight[cell]))
     }
 
     pub fn resize(&self, new_size: UVec2) -> Grid<f32> {
@@ -54,18 +288,156 @@ impl Grid<f32> {
         res
     }
 
-    pub fn min_value(&self) -> f32 {
-        self.values().copied().fold(f32::INFINITY, f32::min)
+    /// Resamples the grid under `out = matrix * src + translation`. Cells that
+    /// map outside of the source are NaN.
+    pub fn warp_affine(&self, matrix: Mat2, translation: Vec2, out_size: UVec2) -> Grid<f32> {
+        let _scope = info_span!("warp_affine").entered();
+
+        let inverse = matrix.inverse();
+        let min = self.origin.as_vec2();
+        let max = min + (self.size.as_vec2() - 1.0).max(Vec2::ZERO);
+
+        Grid::par_from_fn(out_size, |cell| {
+            let pos = inverse * (cell.as_vec2() - translation);
+            if pos.cmplt(min - 1e-4).any() || pos.cmpgt(max + 1e-4).any() {
+                return f32::NAN;
+            }
+
+            self.sample(pos.clamp(min, max))
+        })
     }
 
-    pub fn max_value(&self) -> f32 {
-        self.values().copied().fold(f32::NEG_INFINITY, f32::max)
+    /// Averages non-NaN values in `factor`x`factor` blocks. The origin is
+    /// divided by `factor`, rounding down.
+    pub fn downsample_avg(&self, factor: u32) -> Grid<f32> {
+        let _scope = info_span!("downsample_avg").entered();
+
+        let factor = factor.max(1);
+        let new_size = (self.size + factor - 1) / factor;
+        let new_origin = self.origin.div_euclid(IVec2::splat(factor as i32));
+
+        Grid::par_from_fn_with_origin(new_size, new_origin, |cell| {
+            let start = self.origin + (cell - new_origin) * factor as i32;
+
+            let mut sum = 0.0;
+            let mut count = 0;
+
+            for y in 0..factor as i32 {
+                for x in 0..factor as i32 {
+                    match self.get(start + IVec2::new(x, y)) {
+                        Some(v) if !v.is_nan() => {
+                            sum += v;
+                            count += 1;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            if count > 0 {
+                sum / count as f32
+            } else {
+                f32::NAN
+            }
+        })
+    }
+
+    /// Marching squares over cell centers. Squares touching NaN are skipped.
+    pub fn contour(&self, level: f32) -> Vec<(Vec2, Vec2)> {
+        let _scope = info_span!("contour").entered();
+
+        let mut segments = Vec::new();
+
+        for y in 0..self.size.y as i32 - 1 {
+            for x in 0..self.size.x as i32 - 1 {
+                let cell = self.origin + IVec2::new(x, y);
+                let tl = self[cell];
+                let tr = self[cell + IVec2::new(1, 0)];
+                let br = self[cell + IVec2::new(1, 1)];
+                let bl = self[cell + IVec2::new(0, 1)];
+
+                if tl.is_nan() || tr.is_nan() || br.is_nan() || bl.is_nan() {
+                    continue;
+                }
+
+                let case = u8::from(tl > level)
+                    | u8::from(tr > level) << 1
+                    | u8::from(br > level) << 2
+                    | u8::from(bl > level) << 3;
+
+                let pos = cell.as_vec2();
+                let interp = |a: f32, b: f32, pa: Vec2, pb: Vec2| {
+                    let t = ((level - a) / (b - a)).clamp(0.0, 1.0);
+                    pos + pa.lerp(pb, t)
+                };
+
+                let top = || interp(tl, tr, Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0));
+                let right = || interp(tr, br, Vec2::new(1.0, 0.0), Vec2::new(1.0, 1.0));
+                let bottom = || interp(bl, br, Vec2::new(0.0, 1.0), Vec2::new(1.0, 1.0));
+                let left = || interp(tl, bl, Vec2::new(0.0, 0.0), Vec2::new(0.0, 1.0));
+
+                let center_above = (tl + tr + br + bl) * 0.25 > level;
+
+                match case {
+                    1 | 14 => segments.push((left(), top())),
+                    2 | 13 => segments.push((top(), right())),
+                    3 | 12 => segments.push((left(), right())),
+                    4 | 11 => segments.push((right(), bottom())),
+                    6 | 9 => segments.push((top(), bottom())),
+                    7 | 8 => segments.push((bottom(), left())),
+                    5 if center_above => {
+                        segments.push((left(), bottom()));
+                        segments.push((top(), right()));
+                    }
+                    5 => {
+                        segments.push((left(), top()));
+                        segments.push((right(), bottom()));
+                    }
+                    10 if center_above => {
+                        segments.push((left(), top()));
+                        segments.push((right(), bottom()));
+                    }
+                    10 => {
+                        segments.push((top(), right()));
+                        segments.push((bottom(), left()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        segments
+    }
+
+    /// Returns the first cell holding the smallest non-NaN value.
+    pub fn argmin(&self) -> Option<(IVec2, f32)> {
+        self.entries()
+            .filter(|(_, value)| !value.is_nan())
+            .fold(None, |best, (cell, &value)| match best {
+                Some((_, best_value)) if best_value <= value => best,
+                _ => Some((cell, value)),
+            })
+    }
+
+    /// Returns the first cell holding the largest non-NaN value.
+    pub fn argmax(&self) -> Option<(IVec2, f32)> {
+        self.entries()
+            .filter(|(_, value)| !value.is_nan())
+            .fold(None, |best, (cell, &value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((cell, value)),
+            })
     }
 
     pub fn to_bool(&self, cutoff: f32) -> Grid<bool> {
         self.map(|_, &value| value > cutoff)
     }
 
+    pub fn segment(&self, thresholds: &[f32]) -> Grid<u8> {
+        debug_assert!(thresholds.windows(2).all(|w| w[0] <= w[1]));
+        self.par_map(|_, &value| thresholds.partition_point(|&t| t <= value) as u8)
+    }
+
     pub fn map_range(&self, new_min: f32, new_max: f32) -> Grid<f32> {
         let mut grid = self.clone();
         grid.map_range_inplace(new_min, new_max);
@@ -73,22 +445,117 @@ impl Grid<f32> {
     }
 
     pub fn map_range_inplace(&mut self, new_min: f32, new_max: f32) {
-        let min = self.min_value();
-        let max = self.max_value();
+        let min = self.min_value().unwrap_or(0.0);
+        let max = self.max_value().unwrap_or(0.0);
         for val in self.values_mut() {
             *val = (*val - min) / (max - min) * (new_max - new_min) + new_min;
         }
     }
 
+    /// Counts non-NaN values in `bins` equal ranges between the min and max
+    /// value. If all values are equal, they all land in the first bin.
+    pub fn histogram(&self, bins: usize) -> Vec<u32> {
+        let _scope = info_span!("histogram").entered();
+
+        let (Some(min), Some(max)) = (self.min_value(), self.max_value()) else {
+            return Vec::new();
+        };
+
+        if bins == 0 {
+            return Vec::new();
+        }
+
+        let to_bin = |v: f32| {
+            if min >= max {
+                return 0;
+            }
+            (((v - min) / (max - min) * bins as f32) as usize).min(bins - 1)
+        };
+
+        self.par_values()
+            .filter(|v| !v.is_nan())
+            .fold(
+                || vec![0u32; bins],
+                |mut histogram, &v| {
+                    histogram[to_bin(v)] += 1;
+                    histogram
+                },
+            )
+            .reduce(
+                || vec![0u32; bins],
+                |mut a, b| {
+                    for (a, b) in a.iter_mut().zip(b) {
+                        *a += b;
+                    }
+                    a
+                },
+            )
+    }
+
+    /// Linearly interpolated percentile of non-NaN values, `p` in `0..=100`.
+    /// Returns NaN if there are no such values.
+    pub fn percentile(&self, p: f32) -> f32 {
+        let _scope = info_span!("percentile").entered();
+
+        let mut values = self
+            .par_values()
+            .copied()
+            .filter(|v| !v.is_nan())
+            .collect::<Vec<_>>();
+
+        if values.is_empty() {
+            return f32::NAN;
+        }
+
+        values.par_sort_unstable_by(f32::total_cmp);
+
+        let rank = (p / 100.0).clamp(0.0, 1.0) * (values.len() - 1) as f32;
+        let lo = rank.floor() as usize;
+        let hi = rank.ceil() as usize;
+        lerp(values[lo], values[hi], rank - lo as f32)
+    }
+
+    pub fn equalize(&mut self, bins: usize) {
+        let _scope = info_span!("equalize").entered();
+
+        let min = self.min_value().unwrap_or(0.0);
+        let max = self.max_value().unwrap_or(0.0);
+        if bins == 0 || min >= max {
+            return;
+        }
+
+        let to_bin = |v: f32| (((v - min) / (max - min) * bins as f32) as usize).min(bins - 1);
+        let histogram = self.histogram(bins);
+
+        let total = histogram.iter().sum::<u32>() as f32;
+        let cdf = histogram
+            .iter()
+            .scan(0, |acc, &count| {
+                *acc += count;
+                Some(*acc as f32 / total)
+            })
+            .collect::<Vec<_>>();
+
+        self.par_values_mut().for_each(|v| {
+            if !v.is_nan() {
+                *v = min + cdf[to_bin(*v)] * (max - min);
+            }
+        });
+    }
+
     pub fn debug_save(&self, path: impl AsRef<Path>) {
         if !cfg!(debug_assertions) {
             return;
         }
 
-        let _scope = info_span!("debug_save").entered();
+        self.save_png(path).unwrap();
+    }
 
-        let min_value = self.min_value();
-        let max_value = self.max_value();
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let _scope = info_span!("save_png").entered();
+
+        let min_value = self.min_value().unwrap_or(0.0);
+        let max_value = self.max_value().unwrap_or(0.0);
 
         let colors = self.par_map(|_, &v| {
             let min_color = Color::rgb_u8(40, 138, 183).as_rgba_linear();
@@ -108,18 +575,324 @@ impl Grid<f32> {
             ]
         });
 
-        image::save_buffer(
-            path,
-            cast_slice(&colors.data),
-            self.size.x,
-            self.size.y,
-            image::ColorType::Rgb8,
-        )
-        .unwrap();
+        colors.save_png(path)
+    }
+
+    /// Loads an image as its luminance, mapped to `0.0..=1.0`.
+    pub fn load_grayscale(path: impl AsRef<Path>) -> anyhow::Result<Grid<f32>> {
+        let _scope = info_span!("load_grayscale").entered();
+
+        let path = path.as_ref();
+        let image = image::open(path)
+            .with_context(|| format!("failed to load grayscale image {}", path.display()))?
+            .into_luma8();
+
+        let size = UVec2::new(image.width(), image.height());
+        let data = image
+            .into_raw()
+            .into_iter()
+            .map(|v| v as f32 / 255.0)
+            .collect::<Vec<_>>();
+        Ok(Grid::from_data(size, data))
     }
 }
 
 // TODO: move this somewhere else
+fn catmull_rom([p0, p1, p2, p3]: [f32; 4], t: f32) -> f32 {
+    let a = -0.5 * p0 + 1.5 * p1 - 1.5 * p2 + 0.5 * p3;
+    let b = p0 - 2.5 * p1 + 2.0 * p2 - 0.5 * p3;
+    let c = -0.5 * p0 + 0.5 * p2;
+    ((a * t + b) * t + c) * t + p1
+}
+
 fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a * (1.0 - t) + b * t
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    fn one_nan_corner() -> Grid<f32> {
+        Grid::from_data(UVec2::splat(2), [f32::NAN, 1.0, 2.0, 3.0])
+    }
+
+    #[test]
+    fn sample_skip_nan() {
+        let grid = one_nan_corner();
+        assert_eq!(grid.sample_with(Vec2::splat(0.5), SampleMode::SkipNan), 1.0);
+        assert_eq!(grid.sample(Vec2::splat(0.5)), 1.0);
+    }
+
+    #[test]
+    fn sample_average_non_nan() {
+        let grid = one_nan_corner();
+        let v = grid.sample_with(Vec2::splat(0.5), SampleMode::AverageNonNan);
+        assert!((v - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_propagate_nan() {
+        let grid = one_nan_corner();
+        let v = grid.sample_with(Vec2::splat(0.5), SampleMode::PropagateNan);
+        assert!(v.is_nan());
+    }
+
+    #[test]
+    fn sample_treat_nan_as() {
+        let grid = one_nan_corner();
+        let v = grid.sample_with(Vec2::splat(0.5), SampleMode::TreatNanAs(0.0));
+        assert!((v - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sample_grad_bilinear_on_ramp() {
+        let grid = Grid::from_fn(UVec2::splat(8), |cell| {
+            2.0 * cell.x as f32 + 3.0 * cell.y as f32
+        });
+
+        for h in [0.25, 0.5, 1.0, 2.0] {
+            let grad = grid.sample_grad_bilinear(Vec2::new(3.3, 4.1), h);
+            assert!(
+                grad.abs_diff_eq(Vec2::new(2.0, 3.0), 1e-4),
+                "h = {h}: {grad}"
+            );
+        }
+    }
+
+    #[test]
+    fn segment_bands() {
+        let grid = Grid::from_data(UVec2::new(6, 1), [-1.0, 0.0, 0.5, 1.5, 2.0, 5.0]);
+        let bands = grid.segment(&[0.0, 1.0, 2.0]);
+        assert_eq!(bands.data(), [0, 1, 1, 2, 3, 3]);
+    }
+
+    #[test]
+    fn par_gradient_matches_sample_grad() {
+        let grid = Grid::from_fn(UVec2::new(9, 7), |cell| {
+            ((cell.x * 7 + cell.y * 13) % 11) as f32 * 0.5
+        });
+        let gradient = grid.par_gradient();
+
+        for y in 1..6 {
+            for x in 1..8 {
+                let cell = IVec2::new(x, y);
+                let expected = grid.sample_grad(cell.as_vec2());
+                assert!(gradient[cell].abs_diff_eq(expected, 1e-5), "{cell}");
+            }
+        }
+    }
+
+    #[test]
+    fn sample_polyline_over_ramp() {
+        let grid = Grid::from_fn(UVec2::new(10, 3), |cell| cell.x as f32);
+        let samples = grid.sample_polyline(&[Vec2::new(1.0, 1.0), Vec2::new(6.0, 1.0)], 1.0);
+
+        assert_eq!(samples.len(), 6);
+        for (i, v) in samples.into_iter().enumerate() {
+            assert!((v - (i + 1) as f32).abs() < 1e-5, "{i}: {v}");
+        }
+    }
+
+    #[test]
+    fn argmin_argmax_known_cells() {
+        let mut grid = Grid::from_fn(UVec2::new(5, 4), |cell| (cell.x + cell.y) as f32 * 0.1);
+        grid[IVec2::new(3, 1)] = 9.0;
+        grid[IVec2::new(1, 2)] = -4.0;
+        grid[IVec2::new(4, 3)] = f32::NAN;
+
+        assert_eq!(grid.argmax(), Some((IVec2::new(3, 1), 9.0)));
+        assert_eq!(grid.argmin(), Some((IVec2::new(1, 2), -4.0)));
+    }
+
+    #[test]
+    fn argmax_all_nan() {
+        let grid = Grid::new(UVec2::splat(3), f32::NAN);
+        assert_eq!(grid.argmax(), None);
+        assert_eq!(grid.argmin(), None);
+    }
+
+    fn stamped(center: Vec2, op: StampOp) -> Grid<f32> {
+        let brush = Grid::from_data(UVec2::splat(2), [0.5, 2.0, -1.0, 3.0]);
+        let mut grid = Grid::new(UVec2::splat(3), 1.0);
+        grid.stamp(center, &brush, op);
+        grid
+    }
+
+    #[test]
+    fn stamp_ops() {
+        let center = Vec2::splat(2.0);
+        let cases = [
+            (StampOp::Add, [1.5, 3.0, 0.0, 4.0]),
+            (StampOp::Max, [1.0, 2.0, 1.0, 3.0]),
+            (StampOp::Min, [0.5, 1.0, -1.0, 1.0]),
+            (StampOp::Replace, [0.5, 2.0, -1.0, 3.0]),
+        ];
+
+        for (op, [a, b, c, d]) in cases {
+            let expected = [[1.0, 1.0, 1.0], [1.0, a, b], [1.0, c, d]];
+            assert_eq!(stamped(center, op).data(), expected.concat(), "{op:?}");
+        }
+    }
+
+    #[test]
+    fn stamp_clips_at_border() {
+        let grid = stamped(Vec2::splat(3.0), StampOp::Replace);
+        let expected = [[1.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, 0.5]];
+        assert_eq!(grid.data(), expected.concat());
+    }
+
+    #[test]
+    fn fill_circle_area() {
+        let expected = std::f32::consts::PI * 20.0 * 20.0;
+
+        for soft in [false, true] {
+            let mut grid = Grid::new(UVec2::splat(50), 0.0);
+            grid.fill_circle(Vec2::splat(25.0), 20.0, 1.0, soft);
+
+            let area = grid.sum();
+            assert!((area / expected - 1.0).abs() < 0.05, "{soft}: {area}");
+        }
+    }
+
+    #[test]
+    fn sample_cells_skips_zero_weights() {
+        let grid = Grid::from_data(UVec2::new(6, 1), [0.0, 1.0, -2.0, 3.0, f32::NAN, 0.0]);
+        let mut rng = Pcg32::seed_from_u64(0);
+
+        let mut counts = [0; 6];
+        for cell in grid.sample_cells(&mut rng, 4000) {
+            counts[cell.x as usize] += 1;
+        }
+
+        assert_eq!(counts[0] + counts[2] + counts[4] + counts[5], 0);
+        assert!(counts[3] > counts[1] * 2, "{counts:?}");
+
+        let empty = Grid::new(UVec2::splat(3), 0.0);
+        assert!(empty.sample_cells(&mut rng, 10).is_empty());
+    }
+
+    #[test]
+    fn lerp_with_endpoint_weights() {
+        let a = Grid::from_fn(UVec2::new(4, 3), |cell| cell.x as f32);
+        let b = Grid::from_fn(UVec2::new(4, 3), |cell| 10.0 + cell.y as f32);
+
+        let zero = Grid::new(a.size(), 0.0);
+        let one = Grid::new(a.size(), 1.0);
+
+        assert_eq!(a.lerp_with(&b, &zero).data, a.data);
+        assert_eq!(a.lerp_with(&b, &one).data, b.data);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lerp_with_size_mismatch() {
+        let a = Grid::new(UVec2::splat(4), 0.0);
+        let b = Grid::new(UVec2::splat(3), 0.0);
+        a.lerp_with(&b, &a);
+    }
+
+    #[test]
+    fn histogram_of_empty_grid() {
+        let grid = Grid::<f32>::new(UVec2::ZERO, 0.0);
+        assert!(grid.histogram(4).is_empty());
+        assert!(grid.percentile(50.0).is_nan());
+    }
+
+    #[test]
+    fn histogram_of_constant_grid() {
+        let grid = Grid::new(UVec2::new(4, 3), 2.5);
+        assert_eq!(grid.histogram(4), [12, 0, 0, 0]);
+        assert_eq!(grid.percentile(0.0), 2.5);
+        assert_eq!(grid.percentile(100.0), 2.5);
+    }
+
+    #[test]
+    fn percentile_interpolates() {
+        let grid = Grid::from_data(UVec2::new(5, 1), [4.0, f32::NAN, 0.0, 2.0, 1.0]);
+        assert_eq!(grid.percentile(0.0), 0.0);
+        assert_eq!(grid.percentile(50.0), 1.5);
+        assert_eq!(grid.percentile(100.0), 4.0);
+    }
+
+    #[test]
+    fn equalize_flattens_histogram() {
+        let mut grid = Grid::from_fn(UVec2::splat(100), |cell| {
+            ((cell.y * 100 + cell.x) as f32 / 10_000.0).sqrt()
+        });
+        assert_eq!(grid.histogram(4), [625, 1875, 3125, 4375]);
+
+        grid.equalize(64);
+        for count in grid.histogram(4) {
+            assert!(count.abs_diff(2500) < 300, "{count}");
+        }
+    }
+
+    #[test]
+    fn warp_affine_identity() {
+        let grid = Grid::from_fn(UVec2::new(4, 3), |cell| (cell.x + cell.y * 4) as f32);
+        let warped = grid.warp_affine(Mat2::IDENTITY, Vec2::ZERO, grid.size());
+        assert_eq!(warped.data(), grid.data());
+    }
+
+    #[test]
+    fn warp_affine_quarter_turn() {
+        let grid = Grid::from_fn(UVec2::new(4, 3), |cell| (cell.x + cell.y * 4) as f32);
+
+        // (x, y) -> (-y, x), shifted back to non-negative cells
+        let matrix = Mat2::from_cols(Vec2::Y, Vec2::NEG_X);
+        let translation = Vec2::new(2.0, 0.0);
+        let warped = grid.warp_affine(matrix, translation, UVec2::new(3, 4));
+
+        assert_eq!(warped.data(), grid.rotate_cw().data());
+    }
+
+    #[test]
+    fn contour_of_bump_is_closed_loop() {
+        let center = Vec2::splat(4.0);
+        let grid = Grid::from_fn(UVec2::splat(9), |cell| {
+            4.0 - cell.as_vec2().distance(center)
+        });
+
+        let mut segments = grid.contour(1.5);
+        assert!(segments.len() >= 8);
+
+        for &(a, b) in &segments {
+            assert!((a.distance(center) - 2.5).abs() < 0.2, "{a}");
+            assert!((b.distance(center) - 2.5).abs() < 0.2, "{b}");
+        }
+
+        // walk the segments end to end, which must use all of them and return
+        // to the start
+        let (start, mut end) = segments.pop().unwrap();
+        while let Some(i) = segments
+            .iter()
+            .position(|&(a, b)| a.distance(end) < 1e-4 || b.distance(end) < 1e-4)
+        {
+            let (a, b) = segments.swap_remove(i);
+            end = if a.distance(end) < 1e-4 { b } else { a };
+        }
+
+        assert!(segments.is_empty());
+        assert!(end.distance(start) < 1e-4);
+    }
+
+    #[test]
+    fn downsample_avg_keeps_origin() {
+        let data = [
+            [1.0, 3.0, 0.0, 0.0],
+            [5.0, 7.0, 0.0, 8.0],
+            [2.0, 2.0, 9.0, f32::NAN],
+            [2.0, 2.0, 9.0, 9.0],
+        ];
+        let grid = Grid::from_data(UVec2::new(4, 4), data.concat()).with_origin(IVec2::new(-4, 6));
+
+        let small = grid.downsample_avg(2);
+        assert_eq!(small.size(), UVec2::new(2, 2));
+        assert_eq!(small.origin(), IVec2::new(-2, 3));
+        assert_eq!(small.data(), [4.0, 2.0, 2.0, 9.0]);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use rayon::prelude::*;
+
+use super::Grid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    Four,
+    Eight,
+}
+
+impl Grid<bool> {
+    /// Two-pass 4-connected labeling. Background cells get 0, components are
+    /// numbered from 1 in scan order.
+    pub fn connected_components_labeled(&self) -> (Grid<u32>, usize) {
+        self.label_components(Neighborhood::Four)
+    }
+
+    pub fn label_components(&self, neighborhood: Neighborhood) -> (Grid<u32>, usize) {
+        let _scope = info_span!("label_components").entered();
+
+        let width = self.size.x as usize;
+        let mut labels = vec![0u32; self.data.len()];
+        let mut parents = vec![0u32];
+
+        for (i, &value) in self.data.iter().enumerate() {
+            if !value {
+                continue;
+            }
+
+            let x = i % width;
+            let has_up = i >= width;
+            let left = if x > 0 { labels[i - 1] } else { 0 };
+            let up = if has_up { labels[i - width] } else { 0 };
+
+            let mut label = match (left, up) {
+                (0, 0) => 0,
+                (label, 0) | (0, label) => label,
+                (left, up) => union(&mut parents, left, up),
+            };
+
+            if neighborhood == Neighborhood::Eight && has_up {
+                let up_left = if x > 0 { labels[i - width - 1] } else { 0 };
+                let up_right = if x + 1 < width {
+                    labels[i - width + 1]
+                } else {
+                    0
+                };
+
+                for diagonal in [up_left, up_right] {
+                    label = match (label, diagonal) {
+                        (label, 0) | (0, label) => label,
+                        (label, diagonal) => union(&mut parents, label, diagonal),
+                    };
+                }
+            }
+
+            if label == 0 {
+                label = parents.len() as u32;
+                parents.push(label);
+            }
+
+            labels[i] = label;
+        }
+
+        let mut remap = vec![0u32; parents.len()];
+        let mut count = 0;
+
+        for label in 1..parents.len() as u32 {
+            let root = find(&mut parents, label);
+            if remap[root as usize] == 0 {
+                count += 1;
+                remap[root as usize] = count;
+            }
+            remap[label as usize] = remap[root as usize];
+        }
+
+        labels.par_iter_mut().for_each(|label| {
+            *label = remap[*label as usize];
+        });
+
+        let grid = Grid::from_data(self.size, labels).with_origin(self.origin);
+        (grid, count as usize)
+    }
+}
+
+fn find(parents: &mut [u32], mut label: u32) -> u32 {
+    while parents[label as usize] != label {
+        let parent = parents[label as usize];
+        parents[label as usize] = parents[parent as usize];
+        label = parent;
+    }
+    label
+}
+
+fn union(parents: &mut [u32], a: u32, b: u32) -> u32 {
+    let a = find(parents, a);
+    let b = find(parents, b);
+    let root = a.min(b);
+    parents[a.max(b) as usize] = root;
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_blobs_get_distinct_labels() {
+        // the U shape only joins up on its last row
+        let expected = [
+            [1, 0, 1, 0, 0, 0],
+            [1, 0, 1, 0, 2, 2],
+            [1, 1, 1, 0, 0, 2],
+            [0, 0, 0, 0, 0, 0],
+        ];
+        let expected = Grid::<u32>::from_data(UVec2::new(6, 4), expected.concat());
+        let mask = expected.map(|_, &label| label != 0);
+
+        let (labels, count) = mask.connected_components_labeled();
+        assert_eq!(count, 2);
+        assert_eq!(labels.data(), expected.data());
+    }
+
+    #[test]
+    fn diagonal_cells_connect_only_with_eight_neighbors() {
+        let diagonal = Grid::from_fn(UVec2::splat(3), |cell| cell.x == cell.y);
+        let anti_diagonal = Grid::from_fn(UVec2::splat(3), |cell| cell.x + cell.y == 2);
+
+        for grid in [diagonal, anti_diagonal] {
+            let (_, count) = grid.label_components(Neighborhood::Four);
+            assert_eq!(count, 3);
+
+            let (labels, count) = grid.label_components(Neighborhood::Eight);
+            assert_eq!(count, 1);
+            for (cell, &label) in labels.entries() {
+                assert_eq!(label, u32::from(grid[cell]), "{cell}");
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use super::Grid;
+
+impl Grid<f32> {
+    /// Adds `value` times the line coverage to every cell it touches.
+    pub fn draw_aa_line(&mut self, start: Vec2, end: Vec2, value: f32) {
+        aa_line(start, end, |cell, alpha| {
+            if let Some(v) = self.get_mut(cell) {
+                *v += value * alpha;
+            }
+        });
+    }
+}
+
+/// Xiaolin Wu's line algorithm. Calls `callback` with each touched cell and
+/// its coverage, without any bounds checks.
+pub fn aa_line(start: Vec2, end: Vec2, mut callback: impl FnMut(IVec2, f32)) {
+    let mut plot = |x, y, f| callback(IVec2::new(x, y), f);
+
+    let mut x0 = start.x;
+    let mut y0 = start.y;
+    let mut x1 = end.x;
+    let mut y1 = end.y;
+
+    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+
+    if steep {
+        std::mem::swap(&mut x0, &mut y0);
+        std::mem::swap(&mut x1, &mut y1);
+    }
+    if x0 > x1 {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let dy = y1 - y0;
+
+    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+
+    // handle first endpoint
+    let xend = x0.round();
+    let yend = y0 + gradient * (xend - x0);
+    let xgap = 1.0 - (x0 + 0.5).fract();
+    let xpxl1 = xend;
+    let ypxl1 = yend.trunc();
+    if steep {
+        plot(ypxl1 as i32, xpxl1 as i32, (1.0 - yend.fract()) * xgap);
+        plot(ypxl1 as i32 + 1, xpxl1 as i32, yend.fract() * xgap);
+    } else {
+        plot(xpxl1 as i32, ypxl1 as i32, (1.0 - (yend).fract()) * xgap);
+        plot(xpxl1 as i32, ypxl1 as i32 + 1, yend.fract() * xgap);
+    }
+
+    let mut intery = yend + gradient;
+
+    // handle second endpoint
+    let xend = x1.round();
+    let yend = y1 + gradient * (xend - x1);
+    let xgap = (x1 + 0.5).fract();
+    let xpxl2 = xend;
+    let ypxl2 = (yend).trunc();
+    if steep {
+        plot(ypxl2 as i32, xpxl2 as i32, (1.0 - yend.fract()) * xgap);
+        plot(ypxl2 as i32 + 1, xpxl2 as i32, yend.fract() * xgap);
+    } else {
+        plot(xpxl2 as i32, ypxl2 as i32, (1.0 - yend.fract()) * xgap);
+        plot(xpxl2 as i32, ypxl2 as i32 + 1, yend.fract() * xgap);
+    }
+
+    // main loop
+    if steep {
+        for x in (xpxl1 as i32 + 1)..=(xpxl2 as i32 - 1) {
+            plot(intery as i32, x, 1.0 - intery.fract());
+            plot(intery as i32 + 1, x, intery.fract());
+            intery += gradient;
+        }
+    } else {
+        for x in (xpxl1 as i32 + 1)..=(xpxl2 as i32 - 1) {
+            plot(x, intery as i32, 1.0 - intery.fract());
+            plot(x, intery as i32 + 1, intery.fract());
+            intery += gradient
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn diagonal_coverage_is_symmetric() {
+        let mut coverage = HashMap::<IVec2, f32>::default();
+        aa_line(Vec2::new(2.0, 2.5), Vec2::new(10.0, 10.5), |cell, alpha| {
+            *coverage.entry(cell).or_default() += alpha;
+        });
+
+        // the line is point-symmetric around its midpoint (6.0, 6.5)
+        for (&cell, &alpha) in &coverage {
+            let mirrored = IVec2::new(12, 13) - cell;
+            let mirrored_alpha = coverage.get(&mirrored).copied().unwrap_or(0.0);
+            assert!(
+                (alpha - mirrored_alpha).abs() < 1e-5,
+                "{cell} vs {mirrored}"
+            );
+        }
+
+        assert!(coverage[&IVec2::new(10, 11)] > 0.0);
+    }
+}
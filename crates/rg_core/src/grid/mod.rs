@@ -1,9 +1,15 @@
+mod atomic;
 mod blur;
 mod edt;
 mod float_grid;
+mod label;
+mod line;
+mod morphology;
 mod ops;
+mod recorder;
 mod serde_blob;
 
+use std::iter::Sum;
 use std::path::Path;
 
 use bevy::math::Vec2Swizzles;
@@ -12,7 +18,12 @@ use bytemuck::{cast_slice, CheckedBitPattern, NoUninit};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
-pub use self::edt::EdtSettings;
+pub use self::atomic::AtomicGrid;
+pub use self::edt::{DistanceMetric, EdtSettings};
+pub use self::float_grid::{SampleMode, StampOp};
+pub use self::label::Neighborhood;
+pub use self::line::aa_line;
+pub use self::recorder::GridRecorder;
 
 pub const NEIGHBORHOOD_4: [IVec2; 4] = [
     IVec2::new(0, -1),
@@ -32,6 +43,24 @@ pub const NEIGHBORHOOD_8: [IVec2; 8] = [
     IVec2::new(-1, -1),
 ];
 
+/// Absolute cell position, already offset by the grid origin. All `IVec2`
+/// cells taken by `Grid` methods are absolute too; only `data` indices and
+/// `to_raw_index`/`from_raw_index` deal with raw positions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellIndex(pub IVec2);
+
+impl From<IVec2> for CellIndex {
+    fn from(cell: IVec2) -> Self {
+        CellIndex(cell)
+    }
+}
+
+impl From<CellIndex> for IVec2 {
+    fn from(cell: CellIndex) -> Self {
+        cell.0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(serialize = "T: NoUninit", deserialize = "T: CheckedBitPattern"))]
 pub struct Grid<T> {
@@ -78,6 +107,8 @@ impl<T> Grid<T> {
         Grid::from_fn_with_origin(size, IVec2::ZERO, f)
     }
 
+    /// Parallel version of `from_fn_with_origin`. Like it, passes absolute
+    /// cells (offset by `origin`) to `f`.
     pub fn par_from_fn_with_origin(
         size: UVec2,
         origin: IVec2,
@@ -91,7 +122,7 @@ impl<T> Grid<T> {
             .map(move |idx| {
                 let x = (idx % (size.x as usize)) as i32;
                 let y = (idx / (size.x as usize)) as i32;
-                IVec2::new(x, y)
+                origin + IVec2::new(x, y)
             })
             .map(f)
             .collect::<Vec<T>>();
@@ -126,11 +157,23 @@ impl<T> Grid<T> {
         &mut self.data
     }
 
+    /// Index into `data` of an absolute cell, without bounds checking.
     pub fn index(&self, mut cell: IVec2) -> usize {
         cell -= self.origin;
         (cell.y as usize) * (self.size.x as usize) + (cell.x as usize)
     }
 
+    pub fn to_raw_index(&self, cell: impl Into<CellIndex>) -> Option<usize> {
+        let cell = cell.into().0;
+        self.contains_cell(cell).then(|| self.index(cell))
+    }
+
+    pub fn from_raw_index(&self, index: usize) -> CellIndex {
+        let x = (index % (self.size.x as usize)) as i32;
+        let y = (index / (self.size.x as usize)) as i32;
+        CellIndex(self.origin + IVec2::new(x, y))
+    }
+
     pub fn contains_cell(&self, mut cell: IVec2) -> bool {
         cell -= self.origin;
         (cell.x >= 0 && (cell.x as u32) < self.size.x)
@@ -163,10 +206,85 @@ impl<T> Grid<T> {
         &self.data[index]
     }
 
+    /// Mirrors out of bounds cells at the border without repeating the edge,
+    /// so `origin - 1` maps to `origin + 1`.
+    pub fn reflected_get(&self, cell: IVec2) -> &T {
+        fn reflect(v: i32, n: i32) -> i32 {
+            if n <= 1 {
+                return 0;
+            }
+
+            let period = 2 * (n - 1);
+            let v = v.rem_euclid(period);
+            if v >= n {
+                period - v
+            } else {
+                v
+            }
+        }
+
+        let size = self.size.as_ivec2();
+        let rel = cell - self.origin;
+        let cell = self.origin + IVec2::new(reflect(rel.x, size.x), reflect(rel.y, size.y));
+        &self.data[self.index(cell)]
+    }
+
     pub fn set(&mut self, cell: IVec2, value: T) -> Option<T> {
         Some(std::mem::replace(self.get_mut(cell)?, value))
     }
 
+    pub fn is_uniform(&self) -> Option<&T>
+    where
+        T: PartialEq,
+    {
+        let first = self.data.first()?;
+        self.data.iter().all(|v| v == first).then_some(first)
+    }
+
+    /// Smallest value, skipping values that can't be compared (NaN).
+    pub fn min_value(&self) -> Option<T>
+    where
+        T: Copy + PartialOrd,
+    {
+        self.values()
+            .copied()
+            .filter(|v| v.partial_cmp(v).is_some())
+            .fold(None, |min, v| match min {
+                Some(min) if min <= v => Some(min),
+                _ => Some(v),
+            })
+    }
+
+    /// Largest value, skipping values that can't be compared (NaN).
+    pub fn max_value(&self) -> Option<T>
+    where
+        T: Copy + PartialOrd,
+    {
+        self.values()
+            .copied()
+            .filter(|v| v.partial_cmp(v).is_some())
+            .fold(None, |max, v| match max {
+                Some(max) if max >= v => Some(max),
+                _ => Some(v),
+            })
+    }
+
+    pub fn sum(&self) -> T
+    where
+        T: Copy + Sum,
+    {
+        self.values().copied().sum()
+    }
+
+    pub fn bounds_where(&self, pred: impl Fn(&T) -> bool) -> Option<(IVec2, IVec2)> {
+        self.entries()
+            .filter(|(_, value)| pred(value))
+            .fold(None, |bounds, (cell, _)| match bounds {
+                Some((min, max)) => Some((cell.min(min), cell.max(max))),
+                None => Some((cell, cell)),
+            })
+    }
+
     pub fn cells(&self) -> impl Iterator<Item = IVec2> {
         let size = self.size;
         let origin = self.origin;
@@ -174,6 +292,31 @@ impl<T> Grid<T> {
             .flat_map(move |y| (0..size.x as i32).map(move |x| origin + IVec2::new(x, y)))
     }
 
+    /// In-bounds cells in rings of increasing Chebyshev distance from
+    /// `center`.
+    pub fn spiral_cells(&self, center: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        let min = self.origin;
+        let max = self.origin + self.size.as_ivec2() - 1;
+        let max_radius = (center - min).abs().max((max - center).abs()).max_element();
+
+        (0..=max_radius)
+            .flat_map(move |radius| {
+                let side = (2 * radius).max(1);
+                (0..side).flat_map(move |i| {
+                    let corner = center - IVec2::splat(radius);
+                    let edges = [
+                        corner + IVec2::new(i, 0),
+                        corner + IVec2::new(side, i),
+                        corner + IVec2::new(side - i, side),
+                        corner + IVec2::new(0, side - i),
+                    ];
+                    let count = if radius == 0 { 1 } else { 4 };
+                    edges.into_iter().take(count)
+                })
+            })
+            .filter(|&cell| self.contains_cell(cell))
+    }
+
     pub fn par_cells(&self) -> impl IndexedParallelIterator<Item = IVec2> {
         let size = self.size;
         let origin = self.origin;
@@ -286,6 +429,27 @@ impl<T> Grid<T> {
         self.data.chunks_exact(self.size.x as usize)
     }
 
+    /// Cells with the absolute coordinate `y`. Panics if the row is outside
+    /// of the grid.
+    pub fn row(&self, y: i32) -> &[T] {
+        let start = self.row_start(y);
+        &self.data[start..start + self.size.x as usize]
+    }
+
+    pub fn row_mut(&mut self, y: i32) -> &mut [T] {
+        let start = self.row_start(y);
+        &mut self.data[start..start + self.size.x as usize]
+    }
+
+    fn row_start(&self, y: i32) -> usize {
+        let rows = self.origin.y..self.origin.y + self.size.y as i32;
+        assert!(
+            rows.contains(&y),
+            "row {y} lies outside of the grid rows {rows:?}"
+        );
+        self.index(IVec2::new(self.origin.x, y))
+    }
+
     pub fn par_rows(&self) -> impl IndexedParallelIterator<Item = &[T]>
     where
         T: Sync + 'static,
@@ -311,11 +475,170 @@ impl<T> Grid<T> {
         Grid::par_from_fn_with_origin(self.size.yx(), self.origin.yx(), |cell| self[cell.yx()])
     }
 
+    /// Rotates clockwise (with y pointing down), mapping absolute cells
+    /// `(x, y)` to `(-y, x)`.
+    pub fn rotate_cw(&self) -> Grid<T>
+    where
+        T: Send + Sync + Copy + 'static,
+    {
+        let max = self.origin + self.size.as_ivec2() - 1;
+        let origin = IVec2::new(-max.y, self.origin.x);
+        Grid::par_from_fn_with_origin(self.size.yx(), origin, |cell| {
+            self[IVec2::new(cell.y, -cell.x)]
+        })
+    }
+
+    /// Rotates counter-clockwise, mapping absolute cells `(x, y)` to `(y, -x)`.
+    pub fn rotate_ccw(&self) -> Grid<T>
+    where
+        T: Send + Sync + Copy + 'static,
+    {
+        let max = self.origin + self.size.as_ivec2() - 1;
+        let origin = IVec2::new(self.origin.y, -max.x);
+        Grid::par_from_fn_with_origin(self.size.yx(), origin, |cell| {
+            self[IVec2::new(-cell.y, cell.x)]
+        })
+    }
+
+    /// Maps absolute cells `(x, y)` to `(-x, -y)`.
+    pub fn rotate_180(&self) -> Grid<T>
+    where
+        T: Send + Sync + Copy + 'static,
+    {
+        let origin = -(self.origin + self.size.as_ivec2() - 1);
+        Grid::par_from_fn_with_origin(self.size, origin, |cell| self[-cell])
+    }
+
+    /// Mirrors horizontally, mapping absolute cells `(x, y)` to `(-x, y)`.
+    pub fn flip_x(&self) -> Grid<T>
+    where
+        T: Send + Sync + Copy + 'static,
+    {
+        let origin = IVec2::new(-(self.origin.x + self.size.x as i32 - 1), self.origin.y);
+        Grid::par_from_fn_with_origin(self.size, origin, |cell| self[IVec2::new(-cell.x, cell.y)])
+    }
+
+    /// Mirrors vertically, mapping absolute cells `(x, y)` to `(x, -y)`.
+    pub fn flip_y(&self) -> Grid<T>
+    where
+        T: Send + Sync + Copy + 'static,
+    {
+        let origin = IVec2::new(self.origin.x, -(self.origin.y + self.size.y as i32 - 1));
+        Grid::par_from_fn_with_origin(self.size, origin, |cell| self[IVec2::new(cell.x, -cell.y)])
+    }
+
     pub fn transpose_in_place(&mut self)
     where
         T: Send + Sync + Copy + 'static,
     {
-        *self = self.transpose();
+        if self.size.x != self.size.y {
+            *self = self.transpose();
+            return;
+        }
+
+        let n = self.size.x as usize;
+        for y in 0..n {
+            for x in (y + 1)..n {
+                self.data.swap(y * n + x, x * n + y);
+            }
+        }
+
+        self.origin = self.origin.yx();
+    }
+
+    pub fn padded(&self, pad: UVec2, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let size = self.size + pad * 2;
+        let origin = self.origin - pad.as_ivec2();
+        Grid::from_fn_with_origin(size, origin, |cell| {
+            self.get(cell).cloned().unwrap_or_else(|| fill.clone())
+        })
+    }
+
+    /// Copies a window out of the grid. The result keeps absolute cells, so
+    /// its origin is `min`. The window may stick out of the source, those
+    /// cells repeat the nearest edge cell like `clamped_get`. Panics if the
+    /// window doesn't overlap the source at all.
+    pub fn subgrid(&self, min: IVec2, size: UVec2) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let max = min + size.as_ivec2() - 1;
+        let self_max = self.origin + self.size.as_ivec2() - 1;
+        assert!(
+            min.cmple(self_max).all() && max.cmpge(self.origin).all(),
+            "subgrid {min}..={max} lies outside of the grid {}..={self_max}",
+            self.origin,
+        );
+
+        Grid::from_fn_with_origin(size, min, |cell| self.clamped_get(cell).clone())
+    }
+
+    /// Pastes `other` at its origin, skipping cells that fall outside.
+    pub fn blit(&mut self, other: &Grid<T>)
+    where
+        T: Clone,
+    {
+        let min = other.origin.max(self.origin);
+        let max = (other.origin + other.size.as_ivec2()).min(self.origin + self.size.as_ivec2());
+        if min.cmpge(max).any() {
+            return;
+        }
+
+        let width = (max.x - min.x) as usize;
+        for y in min.y..max.y {
+            let src = other.index(IVec2::new(min.x, y));
+            let dst = self.index(IVec2::new(min.x, y));
+            self.data[dst..dst + width].clone_from_slice(&other.data[src..src + width]);
+        }
+    }
+
+    /// Cells whose value in `other` differs from `self`, with the new values.
+    pub fn diff(&self, other: &Grid<T>) -> Vec<(IVec2, T)>
+    where
+        T: PartialEq + Clone,
+    {
+        assert_eq!(self.size, other.size);
+        assert_eq!(self.origin, other.origin);
+
+        self.entries()
+            .zip(other.values())
+            .filter(|((_, a), b)| a != b)
+            .map(|((cell, _), b)| (cell, b.clone()))
+            .collect()
+    }
+
+    pub fn apply_diff(&mut self, diff: impl IntoIterator<Item = (IVec2, T)>) {
+        for (cell, value) in diff {
+            self[cell] = value;
+        }
+    }
+
+    /// Marks all cells 4-connected to `start` through cells matching the
+    /// predicate.
+    pub fn flood_fill(&self, start: IVec2, predicate: impl Fn(&T) -> bool) -> Grid<bool> {
+        let _scope = info_span!("flood_fill").entered();
+
+        let mut mask = Grid::new(self.size, false).with_origin(self.origin);
+        if !self.get(start).is_some_and(&predicate) {
+            return mask;
+        }
+
+        let mut stack = vec![start];
+        mask[start] = true;
+
+        while let Some(cell) = stack.pop() {
+            for (_, neighbor) in self.neighborhood_4(cell) {
+                if !mask[neighbor] && predicate(&self[neighbor]) {
+                    mask[neighbor] = true;
+                    stack.push(neighbor);
+                }
+            }
+        }
+
+        mask
     }
 }
 
@@ -325,7 +648,11 @@ impl Grid<[u8; 3]> {
             return;
         }
 
-        let _scope = info_span!("debug_save").entered();
+        self.save_png(path).unwrap();
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let _scope = info_span!("save_png").entered();
 
         image::save_buffer(
             path,
@@ -333,8 +660,9 @@ impl Grid<[u8; 3]> {
             self.size.x,
             self.size.y,
             image::ColorType::Rgb8,
-        )
-        .unwrap();
+        )?;
+
+        Ok(())
     }
 }
 
@@ -343,7 +671,350 @@ impl Grid<bool> {
         self.map(|_, &v| if v { 1.0 } else { 0.0 })
     }
 
+    pub fn area(&self) -> usize {
+        self.par_values().filter(|&&v| v).count()
+    }
+
+    pub fn centroid(&self) -> Option<Vec2> {
+        let (sum, count) = self
+            .entries()
+            .filter(|(_, &v)| v)
+            .fold((Vec2::ZERO, 0), |(sum, count), (cell, _)| {
+                (sum + cell.as_vec2(), count + 1)
+            });
+
+        (count > 0).then(|| sum / count as f32)
+    }
+
+    pub fn neighbor_count(&self, neighborhood: &[IVec2]) -> Grid<u8> {
+        self.par_map(|cell, _| {
+            neighborhood
+                .iter()
+                .filter(|&&dir| self.get(cell + dir).copied().unwrap_or(false))
+                .count() as u8
+        })
+    }
+
+    /// Runs one step of a life-like automaton over the 8-neighborhood, e.g.
+    /// `birth: &[3], survive: &[2, 3]` for Conway's rule.
+    pub fn cellular_automata_step(&self, birth: &[u8], survive: &[u8]) -> Grid<bool> {
+        let counts = self.neighbor_count(&NEIGHBORHOOD_8);
+        self.par_map(|cell, &alive| {
+            let count = counts[cell];
+            if alive {
+                survive.contains(&count)
+            } else {
+                birth.contains(&count)
+            }
+        })
+    }
+
     pub fn debug_save(&self, path: impl AsRef<Path>) {
         self.to_f32().debug_save(path);
     }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        self.to_f32().save_png(path)
+    }
+}
+
+impl Grid<[u8; 3]> {
+    pub fn checkerboard(size: UVec2, a: [u8; 3], b: [u8; 3], tile: u32) -> Grid<[u8; 3]> {
+        let tile = tile.max(1) as i32;
+        Grid::from_fn(size, |cell| {
+            let cell = cell / tile;
+            if (cell.x + cell.y) % 2 == 0 {
+                a
+            } else {
+                b
+            }
+        })
+    }
+
+    pub fn gradient(size: UVec2, axis: Vec2) -> Grid<[u8; 3]> {
+        let axis = axis.normalize_or_zero();
+        let max = (size.as_vec2() - 1.0).max(Vec2::ZERO);
+        let extent = max.dot(axis.abs()).max(1.0);
+        let start = Vec2::select(axis.cmplt(Vec2::ZERO), max, Vec2::ZERO);
+        Grid::from_fn(size, |cell| {
+            let t = (cell.as_vec2() - start).dot(axis) / extent;
+            let v = (t.clamp(0.0, 1.0) * 255.0).round() as u8;
+            [v, v, v]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_uniform() {
+        let constant = Grid::new(UVec2::new(4, 3), 7);
+        assert_eq!(constant.is_uniform(), Some(&7));
+
+        let mut varied = constant.clone();
+        varied[IVec2::new(2, 1)] = 8;
+        assert_eq!(varied.is_uniform(), None);
+    }
+
+    #[test]
+    fn par_from_fn_passes_absolute_cells() {
+        let size = UVec2::new(4, 3);
+        let origin = IVec2::new(-2, 5);
+
+        let serial = Grid::from_fn_with_origin(size, origin, |cell| cell);
+        let parallel = Grid::par_from_fn_with_origin(size, origin, |cell| cell);
+        assert_eq!(serial.data, parallel.data);
+
+        let transposed = serial.transpose();
+        assert_eq!(transposed.origin(), origin.yx());
+        for (cell, &value) in transposed.entries() {
+            assert_eq!(value, cell.yx());
+        }
+    }
+
+    #[test]
+    fn raw_index_round_trip() {
+        let grid = Grid::new(UVec2::new(5, 3), 0u8).with_origin(IVec2::new(-3, 7));
+
+        for index in 0..grid.data.len() {
+            let cell = grid.from_raw_index(index);
+            assert!(grid.contains_cell(cell.0));
+            assert_eq!(grid.to_raw_index(cell), Some(index));
+        }
+
+        assert_eq!(grid.from_raw_index(0), CellIndex(IVec2::new(-3, 7)));
+        assert_eq!(grid.to_raw_index(IVec2::new(1, 9)), Some(14));
+        assert_eq!(grid.to_raw_index(IVec2::ZERO), None);
+    }
+
+    #[test]
+    fn row_with_origin() {
+        let mut grid = Grid::from_fn_with_origin(UVec2::new(3, 4), IVec2::new(5, -2), |cell| {
+            cell.x * 10 + cell.y
+        });
+
+        assert_eq!(grid.row(-2), [48, 58, 68]);
+        assert_eq!(grid.row(1), [51, 61, 71]);
+
+        grid.row_mut(0).fill(0);
+        assert_eq!(grid[IVec2::new(6, 0)], 0);
+        assert_eq!(grid[IVec2::new(6, 1)], 61);
+    }
+
+    #[test]
+    #[should_panic]
+    fn row_outside_of_grid() {
+        let grid = Grid::new(UVec2::new(3, 4), 0).with_origin(IVec2::new(5, -2));
+        grid.row(2);
+    }
+
+    #[test]
+    fn subgrid_blit_round_trip() {
+        let grid = Grid::from_fn_with_origin(UVec2::new(8, 6), IVec2::new(-4, 2), |cell| {
+            cell.x * 10 + cell.y
+        });
+
+        let sub = grid.subgrid(IVec2::new(-2, 3), UVec2::new(3, 2));
+        assert_eq!(sub.origin(), IVec2::new(-2, 3));
+        assert_eq!(sub.data(), [-17, -7, 3, -16, -6, 4]);
+
+        let mut pasted = Grid::new(grid.size(), 0).with_origin(grid.origin());
+        pasted.blit(&sub);
+        for (cell, &value) in pasted.entries() {
+            let expected = if sub.contains_cell(cell) {
+                grid[cell]
+            } else {
+                0
+            };
+            assert_eq!(value, expected, "{cell}");
+        }
+
+        let mut copy = grid.clone();
+        copy.blit(&sub);
+        assert_eq!(copy.data, grid.data);
+    }
+
+    #[test]
+    fn subgrid_partial_overlap_is_clamped() {
+        let grid = Grid::from_fn(UVec2::new(4, 4), |cell| cell.x + cell.y * 4);
+
+        let sub = grid.subgrid(IVec2::new(2, -1), UVec2::new(4, 2));
+        assert_eq!(sub.data(), [2, 3, 3, 3, 2, 3, 3, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn subgrid_outside_of_grid() {
+        let grid = Grid::new(UVec2::new(4, 4), 0);
+        grid.subgrid(IVec2::new(4, 0), UVec2::new(2, 2));
+    }
+
+    #[test]
+    fn rectangle_area_and_centroid() {
+        let grid = Grid::from_fn(UVec2::new(8, 6), |cell| {
+            (2..=5).contains(&cell.x) && (1..=3).contains(&cell.y)
+        });
+
+        assert_eq!(grid.area(), 4 * 3);
+        assert_eq!(grid.centroid(), Some(Vec2::new(3.5, 2.0)));
+        assert_eq!(Grid::new(UVec2::splat(3), false).centroid(), None);
+    }
+
+    #[test]
+    fn bounds_where_single_cell() {
+        let mut grid = Grid::new(UVec2::new(5, 4), false).with_origin(IVec2::new(-3, 2));
+        assert_eq!(grid.bounds_where(|&v| v), None);
+
+        let cell = IVec2::new(-1, 4);
+        grid[cell] = true;
+        assert_eq!(grid.bounds_where(|&v| v), Some((cell, cell)));
+    }
+
+    #[test]
+    fn checkerboard_alternates_tiles() {
+        const A: [u8; 3] = [255, 0, 0];
+        const B: [u8; 3] = [0, 0, 255];
+
+        let grid = Grid::checkerboard(UVec2::new(4, 4), A, B, 2);
+        let expected = [[A, A, B, B], [A, A, B, B], [B, B, A, A], [B, B, A, A]];
+        assert_eq!(grid.data(), expected.concat());
+    }
+
+    #[test]
+    fn cellular_automata_blinker() {
+        let horizontal = Grid::from_fn(UVec2::splat(5), |cell| {
+            cell.y == 2 && (1..=3).contains(&cell.x)
+        });
+        let vertical = horizontal.transpose();
+
+        let step = horizontal.cellular_automata_step(&[3], &[2, 3]);
+        assert_eq!(step.data(), vertical.data());
+
+        let step = step.cellular_automata_step(&[3], &[2, 3]);
+        assert_eq!(step.data(), horizontal.data());
+    }
+
+    #[test]
+    fn padded_keeps_center_and_fills_border() {
+        let grid = Grid::from_fn(UVec2::new(3, 2), |cell| cell.x + cell.y * 3 + 1)
+            .with_origin(IVec2::new(4, -1));
+        let padded = grid.padded(UVec2::new(2, 1), 0);
+
+        assert_eq!(padded.size(), UVec2::new(7, 4));
+        assert_eq!(padded.origin(), IVec2::new(2, -2));
+
+        let center = padded.subgrid(grid.origin(), grid.size());
+        assert_eq!(center.data(), grid.data());
+        assert_eq!(padded.values().filter(|&&v| v == 0).count(), 7 * 4 - 3 * 2);
+    }
+
+    #[test]
+    fn transpose_in_place_matches_transpose() {
+        for size in [UVec2::new(4, 4), UVec2::new(4, 3)] {
+            let grid =
+                Grid::from_fn(size, |cell| cell.x + cell.y * 4).with_origin(IVec2::new(1, -2));
+            let expected = grid.transpose();
+
+            let mut transposed = grid.clone();
+            let ptr = transposed.data().as_ptr();
+            transposed.transpose_in_place();
+
+            assert_eq!(transposed.size(), expected.size());
+            assert_eq!(transposed.origin(), expected.origin());
+            assert_eq!(transposed.data(), expected.data());
+
+            if size.x == size.y {
+                assert_eq!(transposed.data().as_ptr(), ptr);
+            }
+        }
+    }
+
+    #[test]
+    fn reflected_get_mirrors_without_repeating_edge() {
+        let grid = Grid::from_fn(UVec2::new(4, 3), |cell| cell.x + cell.y * 4)
+            .with_origin(IVec2::new(2, 5));
+
+        let cases = [
+            (IVec2::new(1, 5), IVec2::new(3, 5)),
+            (IVec2::new(2, 4), IVec2::new(2, 6)),
+            (IVec2::new(6, 8), IVec2::new(4, 6)),
+            (IVec2::new(3, 6), IVec2::new(3, 6)),
+        ];
+
+        for (cell, mirrored) in cases {
+            assert_eq!(*grid.reflected_get(cell), grid[mirrored], "{cell}");
+        }
+    }
+
+    #[test]
+    fn flood_fill_stays_inside_ring() {
+        let radius = |cell: IVec2| cell.abs().max_element();
+        let grid =
+            Grid::from_fn_with_origin(UVec2::splat(7), IVec2::splat(-3), |cell| radius(cell) == 2);
+
+        let inside = grid.flood_fill(IVec2::ZERO, |&wall| !wall);
+        for (cell, &filled) in inside.entries() {
+            assert_eq!(filled, radius(cell) < 2, "{cell}");
+        }
+
+        let outside = grid.flood_fill(IVec2::splat(-3), |&wall| !wall);
+        for (cell, &filled) in outside.entries() {
+            assert_eq!(filled, radius(cell) > 2, "{cell}");
+        }
+
+        assert_eq!(grid.flood_fill(IVec2::new(2, 0), |&wall| !wall).area(), 0);
+    }
+
+    #[test]
+    fn apply_diff_reproduces_target() {
+        let base = Grid::from_fn(UVec2::new(4, 3), |cell| cell.x + cell.y * 4)
+            .with_origin(IVec2::new(-1, 2));
+
+        let mut target = base.clone();
+        target[IVec2::new(0, 2)] = 100;
+        target[IVec2::new(2, 4)] = -5;
+
+        let diff = base.diff(&target);
+        assert_eq!(diff, [(IVec2::new(0, 2), 100), (IVec2::new(2, 4), -5)]);
+
+        let mut patched = base.clone();
+        patched.apply_diff(diff);
+        assert_eq!(patched.data(), target.data());
+    }
+
+    #[test]
+    fn spiral_cells_move_outwards() {
+        let grid = Grid::new(UVec2::new(5, 4), 0).with_origin(IVec2::new(1, 1));
+        let center = IVec2::new(2, 2);
+        let cells = grid.spiral_cells(center).collect::<Vec<_>>();
+
+        assert_eq!(cells[0], center);
+
+        let radii = cells
+            .iter()
+            .map(|&cell| (cell - center).abs().max_element())
+            .collect::<Vec<_>>();
+        assert!(radii.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut sorted = cells.clone();
+        sorted.sort_unstable_by_key(|cell| (cell.y, cell.x));
+        assert_eq!(sorted, grid.cells().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn four_rotations_are_identity() {
+        let grid = Grid::from_fn(UVec2::new(4, 3), |cell| cell.x + cell.y * 4)
+            .with_origin(IVec2::new(-1, 2));
+
+        let rotated = grid.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!(rotated.size(), grid.size());
+        assert_eq!(rotated.origin(), grid.origin());
+        assert_eq!(rotated.data(), grid.data());
+
+        let undone = grid.rotate_cw().rotate_ccw();
+        assert_eq!(undone.origin(), grid.origin());
+        assert_eq!(undone.data(), grid.data());
+    }
 }
@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use super::{DistanceMetric, EdtSettings, Grid};
+
+impl Grid<bool> {
+    /// Grows `true` regions by `radius` cells measured with `metric`, so
+    /// `Chebyshev` dilates by a `(2 * radius + 1)` square. Cells outside of the
+    /// grid are `false`.
+    pub fn dilate(&self, radius: f32, metric: DistanceMetric) -> Grid<bool> {
+        let _scope = info_span!("dilate").entered();
+
+        self.distance_to(true, metric)
+            .par_map(|_, &dist| dist <= radius)
+    }
+
+    /// Shrinks `true` regions by `radius` cells measured with `metric`. Cells
+    /// outside of the grid are `false`, so the whole neighborhood must lie
+    /// within the grid for a cell to survive.
+    pub fn erode(&self, radius: f32, metric: DistanceMetric) -> Grid<bool> {
+        let _scope = info_span!("erode").entered();
+
+        self.distance_to(false, metric)
+            .par_map(|_, &dist| dist > radius)
+    }
+
+    pub fn close(&self, radius: f32, metric: DistanceMetric) -> Grid<bool> {
+        self.dilate(radius, metric).erode(radius, metric)
+    }
+
+    pub fn open(&self, radius: f32, metric: DistanceMetric) -> Grid<bool> {
+        self.erode(radius, metric).dilate(radius, metric)
+    }
+
+    /// Distance from every cell to the nearest cell equal to `target`.
+    fn distance_to(&self, target: bool, metric: DistanceMetric) -> Grid<f32> {
+        // `compute_distance` reads raw cells, so drop the origin while padding
+        let padded = self.padded(UVec2::ONE, false).with_origin(IVec2::ZERO);
+        let dist = padded.compute_distance(
+            metric,
+            EdtSettings {
+                invert: target,
+                normalize: false,
+                padding: 0,
+            },
+        );
+
+        dist.subgrid(IVec2::ONE, self.size).with_origin(self.origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn single_pixel() -> Grid<bool> {
+        let mut grid = Grid::new(UVec2::splat(7), false);
+        grid[IVec2::splat(3)] = true;
+        grid
+    }
+
+    #[test]
+    fn dilate_single_pixel() {
+        let dilated = single_pixel().dilate(1.0, DistanceMetric::Chebyshev);
+
+        for (cell, &value) in dilated.entries() {
+            let inside = (cell - IVec2::splat(3)).abs().max_element() <= 1;
+            assert_eq!(value, inside, "{cell}");
+        }
+    }
+
+    #[test]
+    fn erode_undoes_dilate() {
+        let grid = single_pixel();
+        let closed = grid.close(1.0, DistanceMetric::Chebyshev);
+
+        for (cell, &value) in closed.entries() {
+            assert_eq!(value, grid[cell], "{cell}");
+        }
+    }
+
+    fn square(min: i32, max: i32) -> Grid<bool> {
+        Grid::from_fn(UVec2::splat(9), |cell| {
+            cell.min_element() >= min && cell.max_element() <= max
+        })
+    }
+
+    #[test]
+    fn close_fills_hole() {
+        let mut grid = square(2, 6);
+        grid[IVec2::splat(4)] = false;
+
+        let closed = grid.close(1.0, DistanceMetric::Chebyshev);
+        assert_eq!(closed.data(), square(2, 6).data());
+    }
+
+    #[test]
+    fn open_removes_speck() {
+        let mut grid = square(1, 5);
+        grid[IVec2::splat(7)] = true;
+
+        let opened = grid.open(1.0, DistanceMetric::Chebyshev);
+        assert_eq!(opened.data(), square(1, 5).data());
+    }
+
+    #[test]
+    fn erode_treats_outside_as_false() {
+        let grid = Grid::new(UVec2::splat(5), true).with_origin(IVec2::new(-2, 3));
+        let eroded = grid.erode(1.0, DistanceMetric::Chebyshev);
+
+        assert_eq!(eroded.origin(), grid.origin());
+        for (cell, &value) in eroded.entries() {
+            let inner = (cell - IVec2::new(0, 5)).abs().max_element() <= 1;
+            assert_eq!(value, inner, "{cell}");
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use super::Grid;
+
+/// Collects snapshots of an iterative algorithm and writes them out as a
+/// numbered PNG sequence. Does nothing in release builds.
+#[derive(Debug, Default, Clone)]
+pub struct GridRecorder {
+    frames: Vec<Grid<f32>>,
+}
+
+impl GridRecorder {
+    pub fn new() -> GridRecorder {
+        GridRecorder::default()
+    }
+
+    pub fn record(&mut self, grid: &Grid<f32>) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+
+        self.frames.push(grid.clone());
+    }
+
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn save_sequence(&self, dir: impl AsRef<Path>, prefix: &str) -> anyhow::Result<()> {
+        let _scope = info_span!("save_sequence").entered();
+
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        for (i, frame) in self.frames.iter().enumerate() {
+            frame.save_png(dir.join(format!("{prefix}_{i:04}.png")))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_sequence_writes_every_frame() {
+        let dir = std::env::temp_dir().join(format!("rg_grid_recorder_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut recorder = GridRecorder::new();
+        for i in 0..3 {
+            recorder.record(&Grid::new(UVec2::new(4, 3), i as f32));
+        }
+
+        let expected = if cfg!(debug_assertions) { 3 } else { 0 };
+        assert_eq!(recorder.num_frames(), expected);
+
+        recorder.save_sequence(&dir, "frame").unwrap();
+        let files = std::fs::read_dir(&dir).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files, expected);
+    }
+}
@@ -40,6 +40,11 @@ pub struct PixelMaterial {
     pub dither_offset: UVec2,
     #[uniform(0)]
     pub fog_height: f32,
+    /// Multiplied with `color`. Sampled with the image's own sampler, which is
+    /// nearest unless overridden.
+    #[texture(1)]
+    #[sampler(2)]
+    pub texture: Option<Handle<Image>>,
 }
 
 impl Default for PixelMaterial {
@@ -50,6 +55,7 @@ impl Default for PixelMaterial {
             dither_enabled: true,
             dither_offset: UVec2::ZERO,
             fog_height: 0.0,
+            texture: None,
         }
     }
 }
@@ -67,25 +73,31 @@ impl Material for PixelMaterial {
     ) -> Result<(), SpecializedMeshPipelineError> {
         descriptor.primitive.cull_mode = Some(Face::Back);
 
-        if key.bind_group_data.dither_enabled {
-            if let Some(fragment) = descriptor.fragment.as_mut() {
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            if key.bind_group_data.dither_enabled {
                 fragment.shader_defs.push("DITHER_ENABLED".into());
             }
+
+            if key.bind_group_data.textured {
+                fragment.shader_defs.push("TEXTURED".into());
+            }
         }
 
         Ok(())
     }
 }
 
-#[derive(Eq, PartialEq, Hash, Clone)]
+#[derive(Debug, Eq, PartialEq, Hash, Clone)]
 pub struct PixelMaterialKey {
     dither_enabled: bool,
+    textured: bool,
 }
 
 impl From<&PixelMaterial> for PixelMaterialKey {
     fn from(material: &PixelMaterial) -> Self {
         Self {
             dither_enabled: material.dither_enabled,
+            textured: material.texture.is_some(),
         }
     }
 }
@@ -164,3 +176,22 @@ fn replace_standard_material<T: Material>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_changes_pipeline_key() {
+        let untextured = PixelMaterial::default();
+        let textured = PixelMaterial {
+            texture: Some(Handle::default()),
+            ..default()
+        };
+
+        assert_ne!(
+            PixelMaterialKey::from(&untextured),
+            PixelMaterialKey::from(&textured)
+        );
+    }
+}
@@ -16,8 +16,27 @@ pub struct FbmNoiseSettings {
     persistence: f32,
     #[serde(default = "default_lacunarity")]
     lacunarity: f32,
+    /// Map the output to [-1, 1] instead of [0, 1]
+    #[serde(default)]
+    normalized: bool,
+    #[serde(default)]
+    mode: FbmMode,
 }
 
+/// How each octave is shaped before being summed.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FbmMode {
+    #[default]
+    Standard,
+    /// Sharp ridges, `1 - |n|`. Each octave is weighted by the previous one,
+    /// so detail concentrates on the ridges.
+    Ridged,
+    /// Rounded bumps, `|n|`.
+    Billow,
+}
+
+const RIDGED_GAIN: f32 = 2.0;
+
 fn default_octaves() -> usize {
     5
 }
@@ -33,6 +52,10 @@ fn default_lacunarity() -> f32 {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FbmNoise<const N: usize = 1, S: Noise<N> = SimplexNoise<N>> {
     octaves: Vec<Octave<N, S>>,
+    #[serde(default)]
+    normalized: bool,
+    #[serde(default)]
+    mode: FbmMode,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,23 +98,208 @@ impl<const N: usize, S: Noise<N>> FbmNoise<N, S> {
             octave.amplitude /= total_amplitude;
         }
 
-        FbmNoise { octaves }
+        FbmNoise {
+            octaves,
+            normalized: options.normalized,
+            mode: options.mode,
+        }
+    }
+}
+
+impl<S: Noise<1>> FbmNoise<1, S> {
+    /// Samples noise which repeats every `period`. Four samples offset by the
+    /// period are blended bilinearly, so the result is seamless at the edges.
+    pub fn get_tileable(&self, pos: Vec2, period: Vec2) -> f32 {
+        let pos = Vec2::new(pos.x.rem_euclid(period.x), pos.y.rem_euclid(period.y));
+        let t = pos / period;
+
+        let a = self.get(pos)[0];
+        let b = self.get(pos - Vec2::new(period.x, 0.0))[0];
+        let c = self.get(pos - Vec2::new(0.0, period.y))[0];
+        let d = self.get(pos - period)[0];
+
+        let val = (a * (1.0 - t.x) + b * t.x) * (1.0 - t.y) + (c * (1.0 - t.x) + d * t.x) * t.y;
+
+        // bilinear blending of uncorrelated samples flattens the middle of the
+        // tile, stretch it back and clamp the overshoot into the output range
+        let norm = ((1.0 - t.x).powi(2) + t.x.powi(2)) * ((1.0 - t.y).powi(2) + t.y.powi(2));
+        let (mean, min) = if self.normalized {
+            (0.0, -1.0)
+        } else {
+            (0.5, 0.0)
+        };
+        ((val - mean) / norm + mean).clamp(min, 1.0)
+    }
+}
+
+impl<S: Noise<2>> FbmNoise<2, S> {
+    /// Displaces `pos` by up to `strength` world cells along each axis.
+    pub fn get_warped(&self, pos: Vec2, strength: f32) -> Vec2 {
+        let offset = Vec2::from(self.get(pos));
+        let offset = if self.normalized {
+            offset
+        } else {
+            offset * 2.0 - 1.0
+        };
+
+        pos + strength * offset
+    }
+
+    /// Wraps `source` so that its input coordinates are warped by this noise,
+    /// see `get_warped`.
+    pub fn warp_sampler<'a, T: Noise<1>>(
+        &'a self,
+        source: &'a T,
+        strength: f32,
+    ) -> WarpedNoise<'a, S, T> {
+        WarpedNoise {
+            warp: self,
+            source,
+            strength,
+        }
+    }
+}
+
+pub struct WarpedNoise<'a, S: Noise<2>, T: Noise<1>> {
+    warp: &'a FbmNoise<2, S>,
+    source: &'a T,
+    strength: f32,
+}
+
+impl<S: Noise<2>, T: Noise<1>> Noise<1> for WarpedNoise<'_, S, T> {
+    fn get(&self, pos: Vec2) -> [f32; 1] {
+        self.source.get(self.warp.get_warped(pos, self.strength))
     }
 }
 
 impl<const N: usize, S: Noise<N>> Noise<N> for FbmNoise<N, S> {
     fn get(&self, pos: Vec2) -> [f32; N] {
         let mut res = [0.0; N];
+        let mut weights = [1.0; N];
 
         for octave in &self.octaves {
             let val = octave
                 .source
                 .get(octave.rotation.rotate(pos) * octave.frequency + octave.offset);
-            for (res, val) in res.iter_mut().zip(val) {
+            for ((res, weight), val) in res.iter_mut().zip(&mut weights).zip(val) {
+                let val = match self.mode {
+                    FbmMode::Standard => val,
+                    FbmMode::Ridged => {
+                        let signal = (1.0 - (val * 2.0 - 1.0).abs()) * *weight;
+                        *weight = (signal * RIDGED_GAIN).clamp(0.0, 1.0);
+                        signal
+                    }
+                    FbmMode::Billow => (val * 2.0 - 1.0).abs(),
+                };
+
                 *res += val * octave.amplitude;
             }
         }
 
+        if self.normalized {
+            // every octave contributes at most its amplitude
+            let max_amplitude = self.octaves.iter().map(|v| v.amplitude).sum::<f32>();
+            for v in &mut res {
+                *v = (*v / max_amplitude * 2.0 - 1.0).clamp(-1.0, 1.0);
+            }
+        }
+
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    fn noise(normalized: bool, mode: FbmMode) -> FbmNoise {
+        let settings = FbmNoiseSettings {
+            frequency: 0.05,
+            octaves: default_octaves(),
+            persistence: default_persistence(),
+            lacunarity: default_lacunarity(),
+            normalized,
+            mode,
+        };
+
+        FbmNoise::new(&mut Pcg32::seed_from_u64(0), &settings)
+    }
+
+    #[test]
+    fn normalized_stays_in_range() {
+        for mode in [FbmMode::Standard, FbmMode::Ridged, FbmMode::Billow] {
+            let noise = noise(true, mode);
+            let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+
+            for y in 0..100 {
+                for x in 0..100 {
+                    let v = noise.get(Vec2::new(x as f32, y as f32) * 1.7)[0];
+                    min = min.min(v);
+                    max = max.max(v);
+                }
+            }
+
+            assert!(-1.0 <= min && max <= 1.0, "{mode:?}: {min}..{max}");
+        }
+    }
+
+    #[test]
+    fn normalized_remaps_unit_range() {
+        let raw = noise(false, FbmMode::Standard);
+        let normalized = noise(true, FbmMode::Standard);
+
+        for i in 0..100 {
+            let pos = Vec2::new(i as f32 * 3.1, i as f32 * 0.7);
+            let expected = raw.get(pos)[0] * 2.0 - 1.0;
+            assert!((normalized.get(pos)[0] - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn tileable_matches_at_opposite_edges() {
+        let noise = noise(false, FbmMode::Standard);
+        let period = Vec2::new(40.0, 25.0);
+
+        for i in 0..50 {
+            let x = i as f32 * 0.8;
+            let y = i as f32 * 0.5;
+
+            let left = noise.get_tileable(Vec2::new(0.0, y), period);
+            let right = noise.get_tileable(Vec2::new(period.x - 1e-3, y), period);
+            assert!(
+                (left - right).abs() < 1e-2,
+                "x edge at {y}: {left} != {right}"
+            );
+
+            let top = noise.get_tileable(Vec2::new(x, 0.0), period);
+            let bottom = noise.get_tileable(Vec2::new(x, period.y - 1e-3), period);
+            assert!(
+                (top - bottom).abs() < 1e-2,
+                "y edge at {x}: {top} != {bottom}"
+            );
+
+            let pos = Vec2::new(x, y);
+            let wrapped = noise.get_tileable(pos + period * 3.0, period);
+            assert!((noise.get_tileable(pos, period) - wrapped).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn tileable_stays_in_range() {
+        let period = Vec2::new(40.0, 25.0);
+
+        for (normalized, min) in [(false, 0.0), (true, -1.0)] {
+            let noise = noise(normalized, FbmMode::Standard);
+
+            for y in 0..50 {
+                for x in 0..80 {
+                    let v = noise.get_tileable(Vec2::new(x as f32, y as f32) * 0.5, period);
+                    assert!((min..=1.0).contains(&v), "{normalized}: {v}");
+                }
+            }
+        }
+    }
+}
@@ -1,11 +1,19 @@
 mod fbm;
 mod simplex;
+mod value;
+mod worley;
 
 use bevy::prelude::*;
 
-pub use self::fbm::{FbmNoise, FbmNoiseSettings};
-pub use self::simplex::SimplexNoise;
+pub use self::fbm::{FbmMode, FbmNoise, FbmNoiseSettings, WarpedNoise};
+pub use self::simplex::{SimplexNoise, SimplexNoise3};
+pub use self::value::{ValueNoise2, WhiteNoise2};
+pub use self::worley::{WorleyMetric, WorleyNoise};
 
 pub trait Noise<const N: usize> {
     fn get(&self, pos: Vec2) -> [f32; N];
 }
+
+pub trait Noise3 {
+    fn get(&self, pos: Vec3) -> f32;
+}
@@ -2,16 +2,17 @@
 
 use std::num::Wrapping;
 
-use bevy::math::Vec2;
+use bevy::math::{Vec2, Vec3};
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use super::Noise;
+use super::{Noise, Noise3};
 
 const PRIME_X: Wrapping<i64> = Wrapping(0x5205402B9270C86F);
 const PRIME_Y: Wrapping<i64> = Wrapping(0x598CD327003817B5);
+const PRIME_Z: Wrapping<i64> = Wrapping(0x5BCC226E9FA0BACB);
 const HASH_PRIME: Wrapping<i64> = Wrapping(0x53A3F72DEEC546F5);
 
 const RSQUARED_2D: f32 = 2.0 / 3.0;
@@ -19,6 +20,11 @@ const SKEW_2D: f32 = 0.366025403784439;
 const UNSKEW_2D: f32 = -0.21132486540518713;
 const NORMALIZER_2D: f32 = 0.05481866495625118;
 
+const RSQUARED_3D: f32 = 0.6;
+const SKEW_3D: f32 = 1.0 / 3.0;
+const UNSKEW_3D: f32 = 1.0 / 6.0;
+const NORMALIZER_3D: f32 = 32.0;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound(
     serialize = "[f32; N]: Serialize",
@@ -233,6 +239,97 @@ impl<const N: usize> Distribution<SimplexNoise<N>> for Standard {
     }
 }
 
+/// Classic 3D simplex noise, hashed the same way as `SimplexNoise`. Values
+/// are in `[0, 1]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimplexNoise3 {
+    grads: Box<[Vec3]>,
+}
+
+impl SimplexNoise3 {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> SimplexNoise3 {
+        let grads: [Vec3; 256] = std::array::from_fn(|_| loop {
+            let v = Vec3::from_array(random_vector(rng));
+            if v.length_squared() > 1e-4 {
+                break v.normalize() * NORMALIZER_3D;
+            }
+        });
+
+        SimplexNoise3 {
+            grads: Box::new(grads),
+        }
+    }
+
+    #[inline(always)]
+    fn grad(&self, base: [Wrapping<i64>; 3], offset: Vec3) -> f32 {
+        let a = RSQUARED_3D - offset.length_squared();
+        if a <= 0.0 {
+            return 0.0;
+        }
+
+        let [x, y, z] = base;
+        let idx = ((x * PRIME_X) ^ (y * PRIME_Y) ^ (z * PRIME_Z)) * HASH_PRIME;
+        let grad = self.grads[(idx.0 & 0xff) as usize];
+
+        (a * a) * (a * a) * grad.dot(offset)
+    }
+}
+
+impl Noise3 for SimplexNoise3 {
+    fn get(&self, pos: Vec3) -> f32 {
+        // help the optimizer
+        assert!(self.grads.len() == 256);
+
+        let skewed = pos + Vec3::splat((pos.x + pos.y + pos.z) * SKEW_3D);
+        let cell = skewed.floor();
+        let unskewed = cell - Vec3::splat((cell.x + cell.y + cell.z) * UNSKEW_3D);
+        let d0 = pos - unskewed;
+
+        // Pick the simplex containing the point by ordering the offsets.
+        let (o1, o2) = if d0.x >= d0.y {
+            if d0.y >= d0.z {
+                (Vec3::X, Vec3::new(1.0, 1.0, 0.0))
+            } else if d0.x >= d0.z {
+                (Vec3::X, Vec3::new(1.0, 0.0, 1.0))
+            } else {
+                (Vec3::Z, Vec3::new(1.0, 0.0, 1.0))
+            }
+        } else if d0.y < d0.z {
+            (Vec3::Z, Vec3::new(0.0, 1.0, 1.0))
+        } else if d0.x < d0.z {
+            (Vec3::Y, Vec3::new(0.0, 1.0, 1.0))
+        } else {
+            (Vec3::Y, Vec3::new(1.0, 1.0, 0.0))
+        };
+
+        let base = |offset: Vec3| {
+            let v = cell + offset;
+            [
+                Wrapping(v.x as i64),
+                Wrapping(v.y as i64),
+                Wrapping(v.z as i64),
+            ]
+        };
+
+        let d1 = d0 - o1 + Vec3::splat(UNSKEW_3D);
+        let d2 = d0 - o2 + Vec3::splat(2.0 * UNSKEW_3D);
+        let d3 = d0 - Vec3::ONE + Vec3::splat(3.0 * UNSKEW_3D);
+
+        let value = self.grad(base(Vec3::ZERO), d0)
+            + self.grad(base(o1), d1)
+            + self.grad(base(o2), d2)
+            + self.grad(base(Vec3::ONE), d3);
+
+        value * 0.5 + 0.5
+    }
+}
+
+impl Distribution<SimplexNoise3> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> SimplexNoise3 {
+        SimplexNoise3::new(rng)
+    }
+}
+
 #[inline(always)]
 fn add_vector<const N: usize>(dst: &mut [f32; N], val: [f32; N]) {
     for (dst, val) in dst.iter_mut().zip(val) {
@@ -247,3 +344,42 @@ fn random_vector<const N: usize, R: Rng + ?Sized>(rng: &mut R) -> [f32; N] {
     }
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    fn samples() -> impl Iterator<Item = Vec3> {
+        (0..64).map(|i| {
+            let i = i as f32;
+            Vec3::new(i * 0.37, i * 1.13 - 20.0, (i % 7.0) * 2.9)
+        })
+    }
+
+    #[test]
+    fn simplex3_is_deterministic() {
+        let a = SimplexNoise3::new(&mut Pcg32::seed_from_u64(42));
+        let b = SimplexNoise3::new(&mut Pcg32::seed_from_u64(42));
+
+        for pos in samples() {
+            let v = a.get(pos);
+            assert_eq!(v, b.get(pos), "{pos}");
+            assert!((0.0..=1.0).contains(&v), "{pos}: {v}");
+        }
+
+        // pinned, so that changes to the hashing or the rng are noticed
+        let v = a.get(Vec3::new(1.5, -2.25, 3.75));
+        assert!((v - 0.42292506).abs() < 1e-6, "{v}");
+    }
+
+    #[test]
+    fn simplex3_depends_on_seed() {
+        let a = SimplexNoise3::new(&mut Pcg32::seed_from_u64(42));
+        let b = SimplexNoise3::new(&mut Pcg32::seed_from_u64(43));
+
+        assert!(samples().any(|pos| a.get(pos) != b.get(pos)));
+    }
+}
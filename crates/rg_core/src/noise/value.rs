@@ -0,0 +1,117 @@
+use bevy::math::{IVec2, Vec2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::Noise;
+
+/// Smoothly interpolated random values on the integer lattice, in `[-1, 1]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueNoise2 {
+    seed: u64,
+}
+
+impl ValueNoise2 {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> ValueNoise2 {
+        ValueNoise2 { seed: rng.gen() }
+    }
+}
+
+impl Noise<1> for ValueNoise2 {
+    fn get(&self, pos: Vec2) -> [f32; 1] {
+        let base = pos.floor();
+        let cell = base.as_ivec2();
+        let t = pos - base;
+        let t = t * t * (3.0 - 2.0 * t);
+
+        let tl = hash(self.seed, cell);
+        let tr = hash(self.seed, cell + IVec2::new(1, 0));
+        let bl = hash(self.seed, cell + IVec2::new(0, 1));
+        let br = hash(self.seed, cell + IVec2::new(1, 1));
+
+        let top = tl + (tr - tl) * t.x;
+        let bottom = bl + (br - bl) * t.x;
+        [top + (bottom - top) * t.y]
+    }
+}
+
+/// An independent random value in `[-1, 1]` for every integer cell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhiteNoise2 {
+    seed: u64,
+}
+
+impl WhiteNoise2 {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> WhiteNoise2 {
+        WhiteNoise2 { seed: rng.gen() }
+    }
+}
+
+impl Noise<1> for WhiteNoise2 {
+    fn get(&self, pos: Vec2) -> [f32; 1] {
+        [hash(self.seed, pos.floor().as_ivec2())]
+    }
+}
+
+fn hash(seed: u64, cell: IVec2) -> f32 {
+    let mut h = seed ^ (cell.x as u32 as u64) ^ ((cell.y as u32 as u64) << 32);
+
+    // splitmix64 finalizer
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D049BB133111EB);
+    h ^= h >> 31;
+
+    ((h >> 40) as f32) / ((1u64 << 24) as f32) * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    fn positions() -> impl Iterator<Item = Vec2> {
+        (0..500).map(|i| Vec2::new(i as f32 * 0.37 - 50.0, i as f32 * 0.11 - 20.0))
+    }
+
+    #[test]
+    fn deterministic() {
+        let a = ValueNoise2::new(&mut Pcg32::seed_from_u64(7));
+        let b = ValueNoise2::new(&mut Pcg32::seed_from_u64(7));
+        let c = WhiteNoise2::new(&mut Pcg32::seed_from_u64(7));
+        let d = WhiteNoise2::new(&mut Pcg32::seed_from_u64(7));
+
+        for pos in positions() {
+            assert_eq!(a.get(pos), b.get(pos));
+            assert_eq!(c.get(pos), d.get(pos));
+        }
+    }
+
+    #[test]
+    fn value_noise_is_continuous() {
+        let noise = ValueNoise2::new(&mut Pcg32::seed_from_u64(7));
+        let step = Vec2::new(0.01, 0.01);
+
+        for pos in positions() {
+            let [a] = noise.get(pos);
+            let [b] = noise.get(pos + step);
+            assert!((-1.0..=1.0).contains(&a));
+            assert!((a - b).abs() < 0.1, "{pos}: {a} -> {b}");
+        }
+    }
+
+    #[test]
+    fn white_noise_is_not_continuous() {
+        let noise = WhiteNoise2::new(&mut Pcg32::seed_from_u64(7));
+
+        let max_jump = (-50..50)
+            .map(|x| {
+                let [a] = noise.get(Vec2::new(x as f32 - 0.01, 0.5));
+                let [b] = noise.get(Vec2::new(x as f32, 0.5));
+                (a - b).abs()
+            })
+            .fold(0.0, f32::max);
+
+        assert!(max_jump > 0.5);
+    }
+}
@@ -0,0 +1,116 @@
+use bevy::math::{IVec2, Vec2};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::Noise;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorleyMetric {
+    #[default]
+    Euclidean,
+    Manhattan,
+}
+
+/// Cellular noise with one jittered feature point per integer cell.
+///
+/// `Noise<1>` returns the distance to the nearest feature point (F1),
+/// `Noise<2>` additionally returns the difference to the second nearest one
+/// (F2 - F1), which is zero on cell boundaries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorleyNoise {
+    seed: u64,
+    period: Option<IVec2>,
+    metric: WorleyMetric,
+}
+
+impl WorleyNoise {
+    pub fn new<R: Rng + ?Sized>(rng: &mut R) -> WorleyNoise {
+        WorleyNoise {
+            seed: rng.gen(),
+            period: None,
+            metric: WorleyMetric::Euclidean,
+        }
+    }
+
+    /// Noise that repeats every `period` cells.
+    pub fn new_tileable<R: Rng + ?Sized>(rng: &mut R, period: IVec2) -> WorleyNoise {
+        assert!(period.x > 0 && period.y > 0);
+
+        WorleyNoise {
+            period: Some(period),
+            ..WorleyNoise::new(rng)
+        }
+    }
+
+    pub fn with_metric(mut self, metric: WorleyMetric) -> WorleyNoise {
+        self.metric = metric;
+        self
+    }
+
+    fn feature_point(&self, cell: IVec2) -> Vec2 {
+        let key = match self.period {
+            Some(period) => IVec2::new(cell.x.rem_euclid(period.x), cell.y.rem_euclid(period.y)),
+            None => cell,
+        };
+
+        let h = hash(self.seed, key);
+        let jitter = Vec2::new(
+            (h & 0xFFFF_FFFF) as f32 / (1u64 << 32) as f32,
+            (h >> 32) as f32 / (1u64 << 32) as f32,
+        );
+
+        cell.as_vec2() + jitter
+    }
+
+    fn distance(&self, a: Vec2, b: Vec2) -> f32 {
+        match self.metric {
+            WorleyMetric::Euclidean => a.distance(b),
+            WorleyMetric::Manhattan => (a - b).abs().dot(Vec2::ONE),
+        }
+    }
+
+    fn nearest_two(&self, pos: Vec2) -> (f32, f32) {
+        let cell = pos.floor().as_ivec2();
+        let mut f1 = f32::INFINITY;
+        let mut f2 = f32::INFINITY;
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let point = self.feature_point(cell + IVec2::new(dx, dy));
+                let dist = self.distance(pos, point);
+
+                if dist < f1 {
+                    f2 = f1;
+                    f1 = dist;
+                } else if dist < f2 {
+                    f2 = dist;
+                }
+            }
+        }
+
+        (f1, f2)
+    }
+}
+
+impl Noise<1> for WorleyNoise {
+    fn get(&self, pos: Vec2) -> [f32; 1] {
+        let (f1, _) = self.nearest_two(pos);
+        [f1]
+    }
+}
+
+impl Noise<2> for WorleyNoise {
+    fn get(&self, pos: Vec2) -> [f32; 2] {
+        let (f1, f2) = self.nearest_two(pos);
+        [f1, f2 - f1]
+    }
+}
+
+fn hash(seed: u64, cell: IVec2) -> u64 {
+    let mut h = seed ^ (cell.x as u32 as u64) ^ ((cell.y as u32 as u64) << 32);
+
+    // splitmix64 finalizer
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D049BB133111EB);
+    h ^ (h >> 31)
+}
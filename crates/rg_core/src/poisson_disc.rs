@@ -20,7 +20,11 @@ impl PoissonDiscSampling {
         min_dist: f32,
         max_tries: u32,
     ) -> PoissonDiscSampling {
-        Self::new_tileable(rng.gen(), IVec2::ZERO, size, min_dist, max_tries)
+        Self::new_seeded(rng.gen(), size, min_dist, max_tries)
+    }
+
+    pub fn new_seeded(seed: u64, size: Vec2, min_dist: f32, max_tries: u32) -> PoissonDiscSampling {
+        Self::new_tileable(seed, IVec2::ZERO, size, min_dist, max_tries)
     }
 
     pub fn new_tileable(
@@ -63,6 +63,12 @@ impl<T: Stage> ProgressWriter<T> {
     pub fn finish(&mut self) {
         self.tracker.finish();
     }
+
+    /// Serializes the timings sampled so far. Returns `None` if sampling is
+    /// disabled.
+    pub fn bake(&self) -> Option<Vec<u8>> {
+        self.tracker.bake()
+    }
 }
 
 pub struct ProgressStage<'a> {
@@ -99,6 +105,32 @@ impl ProgressTask<'_> {
     pub fn subtask_completed(&self) {
         self.tracker.subtask_completed();
     }
+
+    /// Runs a subtask that reports its own progress within the parent's range.
+    /// Completing it counts as one completed subtask of the parent.
+    pub fn subtask<R>(
+        &self,
+        num_subtasks: usize,
+        callback: impl FnOnce(ProgressSubtask<'_>) -> R,
+    ) -> R {
+        self.tracker.begin_nested(num_subtasks.try_into().unwrap());
+        let res = callback(ProgressSubtask {
+            tracker: self.tracker,
+        });
+        self.tracker.end_nested();
+        self.tracker.subtask_completed();
+        res
+    }
+}
+
+pub struct ProgressSubtask<'a> {
+    tracker: &'a ProgressTracker,
+}
+
+impl ProgressSubtask<'_> {
+    pub fn subtask_completed(&self) {
+        self.tracker.nested_completed();
+    }
 }
 
 pub trait Stage: Copy + Into<u32> + TryFrom<u32> {}
@@ -146,6 +178,7 @@ struct ProgressTracker {
     stage: CachePadded<AtomicU32>,
     counter: CachePadded<AtomicU64>,
     num_subtasks: CachePadded<AtomicU32>,
+    nested_counter: CachePadded<AtomicU64>,
     progress: CachePadded<AtomicU32>,
     samples: Option<Mutex<RuntimeSamples>>,
     baked_samples: BakedSamples,
@@ -157,6 +190,7 @@ impl ProgressTracker {
             stage: CachePadded::new(AtomicU32::new(0)),
             counter: CachePadded::new(AtomicU64::new(0)),
             num_subtasks: CachePadded::new(AtomicU32::new(0)),
+            nested_counter: CachePadded::new(AtomicU64::new(0)),
             progress: CachePadded::new(AtomicU32::new(0)),
             samples: save_path.map(|path| Mutex::new(RuntimeSamples::new(path))),
             baked_samples: data.map(BakedSamples::load).unwrap_or_default(),
@@ -180,6 +214,16 @@ impl ProgressTracker {
         ((counter >> 32) as u32, counter as u32)
     }
 
+    fn get_nested_progress(&self) -> f32 {
+        let counter = self.nested_counter.load(Ordering::Relaxed);
+        let (completed, total) = ((counter >> 32) as u32, counter as u32);
+        if total == 0 {
+            return 0.0;
+        }
+
+        (completed.min(total) as f32) / (total as f32)
+    }
+
     fn get_old_progress(&self) -> f32 {
         (self.progress.load(Ordering::Relaxed) as f32) / (u32::MAX as f32)
     }
@@ -199,7 +243,8 @@ impl ProgressTracker {
             return old_progress;
         };
 
-        let task_progress = (subtask as f32) / (num_subtasks as f32);
+        let subtask_progress = (subtask as f32) + self.get_nested_progress();
+        let task_progress = (subtask_progress / (num_subtasks as f32)).min(1.0);
         let stage_progress = task_range.start + task_progress * (task_range.end - task_range.start);
         let progress = stage_range.start + stage_progress * (stage_range.end - stage_range.start);
 
@@ -214,6 +259,7 @@ impl ProgressTracker {
     fn begin_task(&self, num_subtasks: u32) {
         let counter = self.counter.fetch_and(!((1 << 32) - 1), Ordering::Relaxed);
         self.num_subtasks.store(num_subtasks, Ordering::Relaxed);
+        self.nested_counter.store(0, Ordering::Relaxed);
         if let Some(samples) = &self.samples {
             Self::sample_begin_task(samples, &self.stage, counter);
         }
@@ -223,6 +269,19 @@ impl ProgressTracker {
         self.counter.fetch_add(1, Ordering::Relaxed);
     }
 
+    fn begin_nested(&self, num_subtasks: u32) {
+        self.nested_counter
+            .store(num_subtasks as u64, Ordering::Relaxed);
+    }
+
+    fn nested_completed(&self) {
+        self.nested_counter.fetch_add(1 << 32, Ordering::Relaxed);
+    }
+
+    fn end_nested(&self) {
+        self.nested_counter.store(0, Ordering::Relaxed);
+    }
+
     fn end_task(&self) {
         let counter = self.counter.fetch_add(1 << 32, Ordering::Relaxed);
         if let Some(samples) = &self.samples {
@@ -256,6 +315,17 @@ impl ProgressTracker {
         stage.end_instant = Some(Instant::now());
     }
 
+    fn bake(&self) -> Option<Vec<u8>> {
+        let samples = self.samples.as_ref()?.lock();
+        match samples.bake().to_bytes() {
+            Ok(data) => Some(data),
+            Err(e) => {
+                error!("failed to bake progress data: {e:?}");
+                None
+            }
+        }
+    }
+
     fn finish(&self) {
         if let Some(samples) = &self.samples {
             let samples = samples.lock();
@@ -372,12 +442,49 @@ impl BakedSamples {
         })
     }
 
+    fn to_bytes(&self) -> io::Result<Vec<u8>> {
+        rmp_serde::to_vec(self).map_err(io::Error::other)
+    }
+
     fn save(&self, path: &Path) -> io::Result<()> {
         let mut writer = File::create(path)?;
 
-        let data = rmp_serde::to_vec(self).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let data = self.to_bytes()?;
         writer.write_all(&data)?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_subtask_reports_within_parent_range() {
+        let baked = BakedSamples {
+            stages: [(0, 0.0..1.0)].into_iter().collect(),
+            tasks: [((0, 0), 0.0..0.5), ((0, 1), 0.5..1.0)]
+                .into_iter()
+                .collect(),
+        };
+        let data = baked.to_bytes().unwrap();
+        let (reader, mut writer) = new_progress_tracker::<u32>(None::<PathBuf>, Some(&data));
+
+        let mut percentages = Vec::new();
+        writer.stage(0).multi_task(2, |task| {
+            task.subtask(4, |subtask| {
+                for _ in 0..4 {
+                    subtask.subtask_completed();
+                    percentages.push(reader.percentage());
+                }
+            });
+            percentages.push(reader.percentage());
+        });
+
+        let expected = [6.25, 12.5, 18.75, 25.0, 25.0];
+        for (&percentage, expected) in percentages.iter().zip(expected) {
+            assert!((percentage - expected).abs() < 1e-3, "{percentages:?}");
+        }
+    }
+}
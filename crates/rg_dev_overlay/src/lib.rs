@@ -1,14 +1,20 @@
+mod log_panel;
 mod version_overlay;
 
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
-use bevy::window::PrimaryWindow;
+use bevy::window::{PresentMode, PrimaryWindow};
 use bevy_egui::egui::{self, pos2, Color32, Frame, Rounding};
 use bevy_egui::EguiContext;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_rapier3d::render::DebugRenderContext as RapierDebugRenderContext;
 use egui_plot::{Line, Plot};
+use rg_core::chunk::{
+    chunk_pos_to_world, ChunkSpawnCenter, ChunkSpawnRadius, WorldOrigin, CHUNK_SIZE,
+};
 
+use crate::log_panel::LogPanelPlugin;
+pub use crate::log_panel::{update_log_subscriber, LogBuffer, LogLine, LogRingBuffer};
 pub use crate::version_overlay::VersionOverlayPlugin;
 
 pub struct DevOverlayPlugin;
@@ -29,6 +35,7 @@ impl Plugin for DevOverlayPlugin {
             ..default()
         })
         .insert_resource(FrameTimePoints::default())
+        .add_plugins(LogPanelPlugin)
         .add_plugins(
             WorldInspectorPlugin::new()
                 .run_if(|s: Res<DevOverlaySettings>| s.enabled && s.show_inspector),
@@ -44,6 +51,7 @@ impl Plugin for DevOverlayPlugin {
                 ui_left_side
                     .run_if(|s: Res<DevOverlaySettings>| s.enabled)
                     .after(ui_settings),
+                draw_chunk_grid.run_if(|s: Res<DevOverlaySettings>| s.enabled && s.show_chunk_grid),
             ),
         );
     }
@@ -58,6 +66,8 @@ pub struct DevOverlaySettings {
     pub show_navmesh: bool,
     pub show_navmesh_heightmap: bool,
     pub show_colliders: bool,
+    pub show_log: bool,
+    pub show_chunk_grid: bool,
 }
 
 fn handle_input(
@@ -148,10 +158,13 @@ fn ui_left_side(
 
 fn ui_settings(
     mut ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut q_sun: Query<(&mut Transform, &mut DirectionalLight)>,
     mut settings: ResMut<DevOverlaySettings>,
     mut gizmo_config: ResMut<GizmoConfig>,
 ) {
     let mut ctx = ctx.single_mut();
+    let mut primary_window = q_window.single_mut();
 
     let window = egui::Window::new("Dev Overlay Settings");
 
@@ -167,5 +180,207 @@ fn ui_settings(
             "Show navigation mesh heightmap",
         );
         ui.checkbox(&mut settings.show_colliders, "Show colliders");
+        ui.checkbox(&mut settings.show_log, "Show log");
+        ui.checkbox(&mut settings.show_chunk_grid, "Show chunk grid");
+
+        let present_mode = primary_window.present_mode;
+        egui::ComboBox::from_label("Present mode")
+            .selected_text(present_mode_label(present_mode))
+            .show_ui(ui, |ui| {
+                for mode in [PresentMode::AutoVsync, PresentMode::AutoNoVsync] {
+                    let label = ui.selectable_label(present_mode == mode, present_mode_label(mode));
+                    if label.clicked() {
+                        set_present_mode(&mut primary_window, mode);
+                    }
+                }
+            });
+
+        if let Ok((mut transform, mut light)) = q_sun.get_single_mut() {
+            ui.separator();
+
+            let (mut azimuth, mut elevation) = sun_angles(transform.rotation);
+            let mut changed = false;
+
+            changed |= ui
+                .add(egui::Slider::new(&mut azimuth, -180.0..=180.0).text("Sun azimuth"))
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut elevation, 1.0..=89.0).text("Sun elevation"))
+                .changed();
+
+            if changed {
+                transform.rotation = sun_rotation(azimuth, elevation);
+            }
+
+            ui.add(
+                egui::Slider::new(&mut light.illuminance, 0.0..=20000.0).text("Sun illuminance"),
+            );
+        }
+    });
+}
+
+/// Rotation of a directional light shining from the given azimuth and
+/// elevation, in degrees. Z is up.
+fn sun_rotation(azimuth: f32, elevation: f32) -> Quat {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    let to_sun = Vec3::new(
+        elevation.cos() * azimuth.cos(),
+        elevation.cos() * azimuth.sin(),
+        elevation.sin(),
+    );
+    Transform::IDENTITY.looking_to(-to_sun, Vec3::Z).rotation
+}
+
+/// Inverse of `sun_rotation`.
+fn sun_angles(rotation: Quat) -> (f32, f32) {
+    let to_sun = rotation * Vec3::Z;
+    let azimuth = to_sun.y.atan2(to_sun.x);
+    let elevation = to_sun.z.clamp(-1.0, 1.0).asin();
+    (azimuth.to_degrees(), elevation.to_degrees())
+}
+
+fn draw_chunk_grid(
+    q_camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    origin: Res<WorldOrigin>,
+    spawn_center: Res<ChunkSpawnCenter>,
+    spawn_radius: Res<ChunkSpawnRadius>,
+    mut gizmos: Gizmos,
+) {
+    let Some((_, camera_transform)) = q_camera
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .max_by_key(|(camera, _)| camera.order)
+    else {
+        return;
+    };
+
+    let height = chunk_grid_height(camera_transform, spawn_center.0) + 0.1;
+
+    for (start, end) in chunk_grid_lines(origin.0, spawn_center.0, spawn_radius.0) {
+        gizmos.line(start.extend(height), end.extend(height), Color::YELLOW);
+    }
+}
+
+/// Height at which the camera's view ray passes over `center`. Looking
+/// straight down, any height works, so sea level is used.
+fn chunk_grid_height(camera_transform: &GlobalTransform, center: Vec2) -> f32 {
+    let pos = camera_transform.translation();
+    let forward = camera_transform.forward();
+
+    let len_sq = forward.xy().length_squared();
+    if len_sq < 1e-6 {
+        return 0.0;
+    }
+
+    let t = (center - pos.xy()).dot(forward.xy()) / len_sq;
+    pos.z + forward.z * t
+}
+
+/// Chunk boundary lines covering a square of `radius` around `center`.
+fn chunk_grid_lines(origin: IVec2, center: Vec2, radius: f32) -> Vec<(Vec2, Vec2)> {
+    let min_chunk = origin + ((center - radius) / CHUNK_SIZE).floor().as_ivec2();
+    let max_chunk = origin + ((center + radius) / CHUNK_SIZE).ceil().as_ivec2();
+
+    let min = chunk_pos_to_world(origin, min_chunk);
+    let max = chunk_pos_to_world(origin, max_chunk);
+
+    let vertical = (min_chunk.x..=max_chunk.x).map(|x| {
+        let x = chunk_pos_to_world(origin, IVec2::new(x, min_chunk.y)).x;
+        (Vec2::new(x, min.y), Vec2::new(x, max.y))
+    });
+
+    let horizontal = (min_chunk.y..=max_chunk.y).map(|y| {
+        let y = chunk_pos_to_world(origin, IVec2::new(min_chunk.x, y)).y;
+        (Vec2::new(min.x, y), Vec2::new(max.x, y))
     });
+
+    vertical.chain(horizontal).collect()
+}
+
+/// Only the automatic present modes are offered. Explicit `Immediate` and
+/// `Mailbox` make the renderer panic when the surface doesn't support them,
+/// and the surface capabilities are only known to the render world.
+/// `AutoNoVsync` tries `Immediate`, then `Mailbox`, and falls back to `Fifo`.
+fn present_mode_label(mode: PresentMode) -> &'static str {
+    match mode {
+        PresentMode::AutoVsync => "VSync",
+        PresentMode::AutoNoVsync => "No VSync (Immediate or Mailbox)",
+        PresentMode::Immediate => "Immediate",
+        PresentMode::Mailbox => "Mailbox",
+        PresentMode::Fifo => "Fifo",
+        PresentMode::FifoRelaxed => "FifoRelaxed",
+    }
+}
+
+/// Avoids touching the window when nothing changes, so the surface isn't
+/// reconfigured.
+fn set_present_mode(window: &mut Mut<Window>, mode: PresentMode) {
+    if window.present_mode != mode {
+        window.present_mode = mode;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn set_present_mode_updates_window() {
+        let mut world = World::new();
+        let window = world.spawn((Window::default(), PrimaryWindow)).id();
+
+        world.run_system_once(|mut q_window: Query<&mut Window, With<PrimaryWindow>>| {
+            set_present_mode(&mut q_window.single_mut(), PresentMode::AutoNoVsync);
+        });
+
+        let window = world.get::<Window>(window).unwrap();
+        assert_eq!(window.present_mode, PresentMode::AutoNoVsync);
+    }
+
+    #[test]
+    fn chunk_grid_lines_cover_radius() {
+        let lines = chunk_grid_lines(IVec2::new(2, -1), Vec2::ZERO, 20.0);
+
+        // chunks -2..2 around the center on both axes, 5 boundaries each
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[0], (Vec2::new(-32.0, -32.0), Vec2::new(-32.0, 32.0)));
+        assert_eq!(lines[4], (Vec2::new(32.0, -32.0), Vec2::new(32.0, 32.0)));
+        assert_eq!(lines[5], (Vec2::new(-32.0, -32.0), Vec2::new(32.0, -32.0)));
+        assert_eq!(lines[9], (Vec2::new(-32.0, 32.0), Vec2::new(32.0, 32.0)));
+
+        for (start, end) in lines {
+            assert_eq!(start % CHUNK_SIZE, Vec2::ZERO);
+            assert_eq!(end % CHUNK_SIZE, Vec2::ZERO);
+        }
+    }
+
+    #[test]
+    fn chunk_grid_height_follows_view_ray() {
+        let camera_transform = GlobalTransform::from(
+            Transform::from_xyz(-10.0, 0.0, 30.0).looking_at(Vec3::new(0.0, 0.0, 20.0), Vec3::Z),
+        );
+        let height = chunk_grid_height(&camera_transform, Vec2::ZERO);
+        assert!((height - 20.0).abs() < 1e-4, "{height}");
+
+        let top_down = GlobalTransform::from(
+            Transform::from_xyz(0.0, 0.0, 30.0).looking_to(-Vec3::Z, Vec3::Y),
+        );
+        assert_eq!(chunk_grid_height(&top_down, Vec2::ZERO), 0.0);
+    }
+
+    #[test]
+    fn sun_rotation_points_light_away_from_sun() {
+        let rotation = sun_rotation(90.0, 30.0);
+        let light_dir = rotation * Vec3::NEG_Z;
+        let expected = -Vec3::new(0.0, 30f32.to_radians().cos(), 0.5);
+        assert!(light_dir.abs_diff_eq(expected, 1e-5), "{light_dir}");
+
+        for (azimuth, elevation) in [(0.0, 10.0), (135.0, 45.0), (-60.0, 80.0)] {
+            let (a, e) = sun_angles(sun_rotation(azimuth, elevation));
+            assert!((a - azimuth).abs() < 1e-3, "{a} {azimuth}");
+            assert!((e - elevation).abs() < 1e-3, "{e} {elevation}");
+        }
+    }
 }
@@ -0,0 +1,243 @@
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use bevy::log::tracing_subscriber::layer::{Context, SubscriberExt};
+use bevy::log::tracing_subscriber::Layer;
+use bevy::log::BoxedSubscriber;
+use bevy::prelude::*;
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::{Event, Level, Subscriber};
+use bevy::window::PrimaryWindow;
+use bevy_egui::egui::{self, Color32, RichText};
+use bevy_egui::EguiContext;
+
+use crate::DevOverlaySettings;
+
+const LOG_CAPACITY: usize = 256;
+
+static LOG_BUFFER: OnceLock<Arc<Mutex<LogRingBuffer>>> = OnceLock::new();
+
+fn global_log_buffer() -> &'static Arc<Mutex<LogRingBuffer>> {
+    LOG_BUFFER.get_or_init(|| Arc::new(Mutex::new(LogRingBuffer::new(LOG_CAPACITY))))
+}
+
+pub fn update_log_subscriber(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    Box::new(subscriber.with(LogLayer {
+        buffer: global_log_buffer().clone(),
+    }))
+}
+
+pub struct LogPanelPlugin;
+
+impl Plugin for LogPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LogBuffer(global_log_buffer().clone()))
+            .insert_resource(LogPanelState {
+                min_level: Level::INFO,
+            })
+            .add_systems(
+                Update,
+                ui_log_panel.run_if(|s: Res<DevOverlaySettings>| s.enabled && s.show_log),
+            );
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+#[derive(Debug)]
+pub struct LogRingBuffer {
+    capacity: usize,
+    lines: VecDeque<LogLine>,
+}
+
+impl LogRingBuffer {
+    pub fn new(capacity: usize) -> LogRingBuffer {
+        LogRingBuffer {
+            capacity,
+            lines: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn push(&mut self, line: LogLine) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+
+        self.lines.push_back(line);
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LogLine> {
+        self.lines.iter()
+    }
+}
+
+#[derive(Resource, Deref)]
+pub struct LogBuffer(pub Arc<Mutex<LogRingBuffer>>);
+
+#[derive(Resource)]
+struct LogPanelState {
+    min_level: Level,
+}
+
+struct LogLayer {
+    buffer: Arc<Mutex<LogRingBuffer>>,
+}
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let line = LogLine {
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            message: visitor.message,
+        };
+
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.push(line);
+        }
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            if !self.message.is_empty() {
+                self.message.push(' ');
+            }
+            self.message += &format!("{}={value:?}", field.name());
+        }
+    }
+}
+
+fn ui_log_panel(
+    mut ctx: Query<&mut EguiContext, With<PrimaryWindow>>,
+    mut state: ResMut<LogPanelState>,
+    buffer: Res<LogBuffer>,
+) {
+    let mut ctx = ctx.single_mut();
+
+    let window = egui::Window::new("Log").default_width(500.0);
+
+    window.show(ctx.get_mut(), |ui| {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("Level")
+                .selected_text(state.min_level.as_str())
+                .show_ui(ui, |ui| {
+                    for level in [
+                        Level::ERROR,
+                        Level::WARN,
+                        Level::INFO,
+                        Level::DEBUG,
+                        Level::TRACE,
+                    ] {
+                        ui.selectable_value(&mut state.min_level, level, level.as_str());
+                    }
+                });
+
+            if ui.button("Clear").clicked() {
+                if let Ok(mut buffer) = buffer.lock() {
+                    buffer.clear();
+                }
+            }
+        });
+
+        let lines = match buffer.lock() {
+            Ok(buffer) => buffer
+                .iter()
+                .filter(|line| line.level <= state.min_level)
+                .cloned()
+                .collect::<Vec<_>>(),
+            Err(_) => return,
+        };
+
+        egui::ScrollArea::vertical()
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &lines {
+                    let color = match line.level {
+                        Level::ERROR => Color32::LIGHT_RED,
+                        Level::WARN => Color32::YELLOW,
+                        Level::INFO => Color32::LIGHT_GREEN,
+                        _ => Color32::GRAY,
+                    };
+
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(RichText::new(line.level.as_str()).color(color).monospace());
+                        ui.label(RichText::new(&line.target).weak());
+                        ui.label(&line.message);
+                    });
+                }
+            });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(message: &str) -> LogLine {
+        LogLine {
+            level: Level::INFO,
+            target: "test".to_owned(),
+            message: message.to_owned(),
+        }
+    }
+
+    #[test]
+    fn ring_buffer_caps_at_capacity() {
+        let mut buffer = LogRingBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(line(&i.to_string()));
+        }
+
+        assert_eq!(buffer.len(), 3);
+        let messages = buffer
+            .iter()
+            .map(|l| l.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["2", "3", "4"]);
+    }
+
+    #[test]
+    fn zero_capacity_drops_everything() {
+        let mut buffer = LogRingBuffer::new(0);
+        buffer.push(line("dropped"));
+        assert!(buffer.is_empty());
+    }
+}
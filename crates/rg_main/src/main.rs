@@ -1,6 +1,7 @@
 use bevy::core_pipeline::prepass::{DepthPrepass, NormalPrepass};
 use bevy::core_pipeline::tonemapping::DebandDither;
 use bevy::ecs::schedule::{LogLevel, ScheduleBuildSettings};
+use bevy::log::LogPlugin;
 use bevy::math::Vec3Swizzles;
 use bevy::pbr::{CascadeShadowConfigBuilder, DirectionalLightShadowMap};
 use bevy::prelude::*;
@@ -13,7 +14,7 @@ use rg_ai::AiPlugin;
 use rg_core::chunk::{ChunkSpawnCenter, FloatingOrigin, WorldOrigin, CHUNK_SIZE};
 use rg_core::material::PixelMaterial;
 use rg_core::{CameraController, CollisionLayers, CorePlugins};
-use rg_dev_overlay::{DevOverlayPlugin, VersionOverlayPlugin};
+use rg_dev_overlay::{update_log_subscriber, DevOverlayPlugin, VersionOverlayPlugin};
 use rg_navigation::NavigationPlugin;
 use rg_terrain::TerrainPlugin;
 use rg_worldgen::WorldgenPlugin;
@@ -37,6 +38,10 @@ fn main() {
                     }),
                     ..default()
                 })
+                .set(LogPlugin {
+                    update_subscriber: Some(update_log_subscriber),
+                    ..default()
+                })
                 .set(ImagePlugin::default_nearest())
                 .set(AssetPlugin::default()),
         )
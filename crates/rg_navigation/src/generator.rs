@@ -35,7 +35,7 @@ pub fn generate_chunk(
     let connections = generate_connections(settings, &height_map);
     let mut edges = generate_edges(&connections);
     sort_edges(&mut edges);
-    let triangles = triangulate(&edges);
+    let triangles = triangulate(&edges, settings.merge_polygons);
 
     NavMeshChunk {
         is_empty: false,
@@ -274,7 +274,7 @@ fn join_edges(edges: impl Iterator<Item = (Vec2, Vec2)>) -> Vec<(Vec2, Vec2)> {
     res_edges
 }
 
-fn triangulate(edges: &[(Vec2, Vec2)]) -> Vec<Triangle> {
+fn triangulate(edges: &[(Vec2, Vec2)], merge_polygons: bool) -> Vec<Triangle> {
     fn point2(v: Vec2) -> Point2<f32> {
         Point2::new(v.x, v.y)
     }
@@ -326,7 +326,7 @@ fn triangulate(edges: &[(Vec2, Vec2)]) -> Vec<Triangle> {
         face_to_triangle.insert(handle, triangles.len() as u32);
 
         triangles.push(Triangle {
-            vertices: face.positions().map(point2_to_vec2),
+            vertices: face.positions().map(point2_to_vec2).into(),
             links: SmallVec::new(),
         });
     }
@@ -404,5 +404,215 @@ fn triangulate(edges: &[(Vec2, Vec2)]) -> Vec<Triangle> {
         triangles[triangle_idx as usize].links.extend(links);
     }
 
-    triangles
+    if !merge_polygons {
+        return triangles;
+    }
+
+    let constraints = constraint_edges
+        .iter()
+        .map(|&edge| {
+            let [start, end] = triangulation.directed_edge(edge).positions();
+            (
+                point2_to_vec2(start).to_bits(),
+                point2_to_vec2(end).to_bits(),
+            )
+        })
+        .collect();
+
+    merge_triangles(&triangles, &constraints)
+}
+
+/// Greedily merges triangles across non-constraint edges as long as the
+/// result stays convex (Hertel-Mehlhorn), then rebuilds the links.
+fn merge_triangles(triangles: &[Triangle], constraints: &HashSet<(UVec2, UVec2)>) -> Vec<Triangle> {
+    let _span = info_span!("merge_triangles").entered();
+
+    let mut polygons = triangles
+        .iter()
+        .map(|triangle| Some(triangle.vertices.clone()))
+        .collect::<Vec<_>>();
+    let mut owners = (0..triangles.len()).collect::<Vec<_>>();
+    let mut members = (0..triangles.len()).map(|i| vec![i]).collect::<Vec<_>>();
+
+    for (triangle_idx, triangle) in triangles.iter().enumerate() {
+        for link in &triangle.links {
+            if (link.opposite_triangle as usize) < triangle_idx {
+                continue;
+            }
+
+            let [start, end] = link.segment;
+            if constraints.contains(&(start.to_bits(), end.to_bits()))
+                || constraints.contains(&(end.to_bits(), start.to_bits()))
+            {
+                continue;
+            }
+
+            let a = owners[triangle_idx];
+            let b = owners[link.opposite_triangle as usize];
+            if a == b {
+                continue;
+            }
+
+            let (Some(poly_a), Some(poly_b)) = (&polygons[a], &polygons[b]) else {
+                continue;
+            };
+
+            let Some(merged) = merge_convex(poly_a, poly_b, start, end) else {
+                continue;
+            };
+
+            polygons[a] = Some(merged);
+            polygons[b] = None;
+
+            for member in std::mem::take(&mut members[b]) {
+                owners[member] = a;
+                members[a].push(member);
+            }
+        }
+    }
+
+    let mut polygons = polygons
+        .into_iter()
+        .flatten()
+        .map(|vertices| Triangle {
+            vertices,
+            links: SmallVec::new(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut edge_map = HashMap::new();
+    for (polygon_idx, polygon) in polygons.iter().enumerate() {
+        let vertices = &polygon.vertices;
+        for (edge_idx, &start) in vertices.iter().enumerate() {
+            let end = vertices[(edge_idx + 1) % vertices.len()];
+            edge_map.insert(
+                (start.to_bits(), end.to_bits()),
+                (polygon_idx as u32, edge_idx as u8),
+            );
+        }
+    }
+
+    for polygon in &mut polygons {
+        let vertices = &polygon.vertices;
+        for (edge_idx, &start) in vertices.iter().enumerate() {
+            let end = vertices[(edge_idx + 1) % vertices.len()];
+            let Some(&(opposite_triangle, opposite_edge)) =
+                edge_map.get(&(end.to_bits(), start.to_bits()))
+            else {
+                continue;
+            };
+
+            polygon.links.push(Link {
+                kind: LinkKind::Internal,
+                segment: [start, end],
+                edge: edge_idx as u8,
+                opposite_triangle,
+                opposite_link: 0,
+                opposite_edge,
+            });
+        }
+    }
+
+    for polygon_idx in 0..polygons.len() {
+        for link_idx in 0..polygons[polygon_idx].links.len() {
+            let link = polygons[polygon_idx].links[link_idx];
+            let opposite_link = polygons[link.opposite_triangle as usize]
+                .links
+                .iter()
+                .position(|v| v.edge == link.opposite_edge)
+                .unwrap_or(0);
+            polygons[polygon_idx].links[link_idx].opposite_link = opposite_link as u8;
+        }
+    }
+
+    polygons
+}
+
+/// Joins two counter-clockwise polygons sharing the `start -> end` edge of `a`.
+/// Returns `None` if the result would be concave.
+fn merge_convex(a: &[Vec2], b: &[Vec2], start: Vec2, end: Vec2) -> Option<SmallVec<[Vec2; 3]>> {
+    let i = (0..a.len()).find(|&i| a[i] == start && a[(i + 1) % a.len()] == end)?;
+    let j = (0..b.len()).find(|&j| b[j] == end && b[(j + 1) % b.len()] == start)?;
+
+    if a.len() + b.len() - 2 > u8::MAX as usize {
+        return None;
+    }
+
+    let mut merged = SmallVec::<[Vec2; 3]>::with_capacity(a.len() + b.len() - 2);
+    merged.extend((1..=a.len()).map(|k| a[(i + k) % a.len()]));
+    merged.extend((2..b.len()).map(|k| b[(j + k) % b.len()]));
+
+    let n = merged.len();
+    let is_convex = (0..n).all(|k| {
+        let p0 = merged[k];
+        let p1 = merged[(k + 1) % n];
+        let p2 = merged[(k + 2) % n];
+        (p1 - p0).perp_dot(p2 - p1) >= -1e-6
+    });
+
+    is_convex.then_some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+
+    /// Two triangles sharing the diagonal of a 4x2 rectangle.
+    fn rectangle() -> Vec<Triangle> {
+        let link = |segment: [Vec2; 2], edge, opposite_triangle, opposite_edge| Link {
+            kind: LinkKind::Internal,
+            segment,
+            edge,
+            opposite_triangle,
+            opposite_link: 0,
+            opposite_edge,
+        };
+
+        let (a, b, c, d) = (
+            vec2(0.0, 0.0),
+            vec2(4.0, 0.0),
+            vec2(4.0, 2.0),
+            vec2(0.0, 2.0),
+        );
+        vec![
+            Triangle {
+                vertices: smallvec![a, b, c],
+                links: smallvec![link([c, a], 2, 1, 0)],
+            },
+            Triangle {
+                vertices: smallvec![a, c, d],
+                links: smallvec![link([a, c], 0, 0, 2)],
+            },
+        ]
+    }
+
+    #[test]
+    fn rectangle_merges_into_one_polygon() {
+        let polygons = merge_triangles(&rectangle(), &HashSet::new());
+
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(
+            polygons[0].vertices.as_slice(),
+            [
+                vec2(0.0, 0.0),
+                vec2(4.0, 0.0),
+                vec2(4.0, 2.0),
+                vec2(0.0, 2.0)
+            ]
+        );
+        assert!(polygons[0].links.is_empty());
+    }
+
+    #[test]
+    fn constraint_keeps_triangles_apart() {
+        let diagonal = (vec2(4.0, 2.0).to_bits(), vec2(0.0, 0.0).to_bits());
+        let polygons = merge_triangles(&rectangle(), &[diagonal].into_iter().collect());
+
+        assert_eq!(polygons.len(), 2);
+        assert_eq!(polygons[0].links.len(), 1);
+        assert_eq!(polygons[0].links[0].opposite_triangle, 1);
+        assert_eq!(polygons[1].links[0].opposite_triangle, 0);
+    }
 }
@@ -46,6 +46,18 @@ pub struct NavMeshSettings {
     pub agent_height: f32,
     pub agent_radius: f32,
     pub agent_offset: f32,
+    /// Merge adjacent triangles into convex polygons after triangulation
+    pub merge_polygons: bool,
+}
+
+impl NavMeshSettings {
+    pub fn with_agent_size(self, height: f32, radius: f32) -> Self {
+        Self {
+            agent_height: height,
+            agent_radius: radius,
+            ..self
+        }
+    }
 }
 
 impl Default for NavMeshSettings {
@@ -59,6 +71,7 @@ impl Default for NavMeshSettings {
             agent_height: 1.8,
             agent_radius: 0.3,
             agent_offset: 0.05,
+            merge_polygons: true,
         }
     }
 }
@@ -1,3 +1,9 @@
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use bevy::math::Vec3Swizzles;
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 use rg_core::chunk::{chunk_pos_to_world, WorldOrigin, CHUNK_SIZE, CHUNK_TILES};
@@ -19,6 +25,177 @@ impl NavMesh {
     pub fn remove_chunk(&mut self, chunk_pos: IVec2) {
         self.chunks.remove(&chunk_pos);
     }
+
+    pub fn export_obj(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let _scope = info_span!("export_obj").entered();
+
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+
+        let mut chunks = self
+            .chunks
+            .iter()
+            .filter(|(_, chunk)| !chunk.is_empty && !chunk.triangles.is_empty())
+            .collect::<Vec<_>>();
+        chunks.sort_by_key(|(chunk_pos, _)| (chunk_pos.y, chunk_pos.x));
+
+        let mut num_vertices = 0;
+
+        for (&chunk_pos, chunk) in chunks {
+            let chunk_origin = chunk_pos.as_vec2() * CHUNK_SIZE;
+
+            for triangle in &chunk.triangles {
+                for &vertex in &triangle.vertices {
+                    let pos = chunk_origin + vertex;
+                    let height = chunk.sample_height(vertex);
+                    writeln!(writer, "v {} {} {}", pos.x, pos.y, height)?;
+                }
+
+                write!(writer, "f")?;
+                for i in 0..triangle.vertices.len() {
+                    write!(writer, " {}", num_vertices + i + 1)?;
+                }
+                writeln!(writer)?;
+
+                num_vertices += triangle.vertices.len();
+            }
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Finds a path with A* over triangle links. Positions are absolute, without
+    /// the world origin applied. Returns `start`, the centers of the triangles
+    /// in between and `end`, or `None` if either point is off the navmesh or
+    /// they aren't connected.
+    pub fn find_path(&self, start: Vec3, end: Vec3) -> Option<Vec<Vec3>> {
+        let _scope = info_span!("find_path").entered();
+
+        let start_id = self.find_triangle(start.xy())?;
+        let end_id = self.find_triangle(end.xy())?;
+
+        let mut queue = BinaryHeap::new();
+        let mut costs = HashMap::default();
+        let mut came_from = HashMap::default();
+
+        queue.push(QueueItem {
+            priority: 0.0,
+            id: start_id,
+        });
+        costs.insert(start_id, 0.0);
+
+        while let Some(QueueItem { id, .. }) = queue.pop() {
+            if id == end_id {
+                break;
+            }
+
+            let (chunk_pos, triangle_idx) = id;
+            let triangle = &self.chunks[&chunk_pos].triangles[triangle_idx as usize];
+            let center = self.triangle_center(id);
+            let cost = costs[&id];
+
+            for link in &triangle.links {
+                let neighbor_pos = chunk_pos + link.kind.chunk_offset();
+                let Some(neighbor_chunk) = self.chunks.get(&neighbor_pos) else {
+                    continue;
+                };
+
+                if link.opposite_triangle as usize >= neighbor_chunk.triangles.len() {
+                    continue;
+                }
+
+                let neighbor_id = (neighbor_pos, link.opposite_triangle);
+                let neighbor_center = self.triangle_center(neighbor_id);
+                let neighbor_cost = cost + center.distance(neighbor_center);
+
+                if costs
+                    .get(&neighbor_id)
+                    .is_some_and(|&old_cost| old_cost <= neighbor_cost)
+                {
+                    continue;
+                }
+
+                costs.insert(neighbor_id, neighbor_cost);
+                came_from.insert(neighbor_id, id);
+                queue.push(QueueItem {
+                    priority: neighbor_cost + neighbor_center.distance(end.xy()),
+                    id: neighbor_id,
+                });
+            }
+        }
+
+        if start_id != end_id && !came_from.contains_key(&end_id) {
+            return None;
+        }
+
+        let mut path = vec![end];
+        let mut id = end_id;
+
+        while let Some(&prev_id) = came_from.get(&id) {
+            if prev_id != start_id {
+                let (chunk_pos, _) = prev_id;
+                let chunk_origin = chunk_pos.as_vec2() * CHUNK_SIZE;
+                let center = self.triangle_center(prev_id);
+                let height = self.chunks[&chunk_pos].sample_height(center - chunk_origin);
+                path.push(center.extend(height));
+            }
+
+            id = prev_id;
+        }
+
+        path.push(start);
+        path.reverse();
+        Some(path)
+    }
+
+    fn find_triangle(&self, pos: Vec2) -> Option<(IVec2, u32)> {
+        let chunk_pos = (pos / CHUNK_SIZE).floor().as_ivec2();
+        let chunk = self.chunks.get(&chunk_pos)?;
+        let pos = pos - chunk_pos.as_vec2() * CHUNK_SIZE;
+
+        let triangle_idx = chunk.triangles.iter().position(|triangle| {
+            let vertices = &triangle.vertices;
+            (0..vertices.len()).all(|i| {
+                let a = vertices[i];
+                let b = vertices[(i + 1) % vertices.len()];
+                (b - a).perp_dot(pos - a) >= 0.0
+            })
+        })?;
+
+        Some((chunk_pos, triangle_idx as u32))
+    }
+
+    fn triangle_center(&self, (chunk_pos, triangle_idx): (IVec2, u32)) -> Vec2 {
+        let vertices = &self.chunks[&chunk_pos].triangles[triangle_idx as usize].vertices;
+        let center = vertices.iter().sum::<Vec2>() / vertices.len() as f32;
+        chunk_pos.as_vec2() * CHUNK_SIZE + center
+    }
+}
+
+struct QueueItem {
+    priority: f32,
+    id: (IVec2, u32),
+}
+
+impl PartialEq for QueueItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for QueueItem {}
+
+impl PartialOrd for QueueItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        f32::total_cmp(&other.priority, &self.priority)
+    }
 }
 
 #[derive(Debug, Component)]
@@ -36,9 +213,11 @@ impl NavMeshChunk {
     }
 }
 
+/// A convex polygon in counter-clockwise order. Plain triangle unless
+/// [`NavMeshSettings::merge_polygons`](crate::NavMeshSettings) is enabled.
 #[derive(Debug, Clone)]
 pub struct Triangle {
-    pub vertices: [Vec2; 3],
+    pub vertices: SmallVec<[Vec2; 3]>,
     pub links: SmallVec<[Link; 3]>,
 }
 
@@ -61,6 +240,19 @@ pub enum LinkKind {
     NegY,
 }
 
+impl LinkKind {
+    /// Offset of the chunk containing the opposite triangle.
+    pub fn chunk_offset(self) -> IVec2 {
+        match self {
+            LinkKind::Internal => IVec2::ZERO,
+            LinkKind::PosX => IVec2::X,
+            LinkKind::NegX => IVec2::NEG_X,
+            LinkKind::PosY => IVec2::Y,
+            LinkKind::NegY => IVec2::NEG_Y,
+        }
+    }
+}
+
 pub fn draw_navmesh_gizmos(navmesh: Res<NavMesh>, mut gizmos: Gizmos, origin: Res<WorldOrigin>) {
     for (&chunk_pos, chunk) in navmesh.chunks.iter() {
         let chunk_origin = chunk_pos_to_world(origin.0, chunk_pos);
@@ -77,11 +269,12 @@ pub fn draw_navmesh_gizmos(navmesh: Res<NavMesh>, mut gizmos: Gizmos, origin: Re
         };
 
         for triangle in &chunk.triangles {
-            line(triangle.vertices[0], triangle.vertices[1], Color::RED);
-            line(triangle.vertices[1], triangle.vertices[2], Color::RED);
-            line(triangle.vertices[2], triangle.vertices[0], Color::RED);
+            let vertices = &triangle.vertices;
+            for (i, &vertex) in vertices.iter().enumerate() {
+                line(vertex, vertices[(i + 1) % vertices.len()], Color::RED);
+            }
 
-            let center = (triangle.vertices[0] + triangle.vertices[1] + triangle.vertices[2]) / 3.0;
+            let center = vertices.iter().sum::<Vec2>() / vertices.len() as f32;
 
             for link in &triangle.links {
                 let mid = (link.segment[0] + link.segment[1]) * 0.5;
@@ -128,3 +321,38 @@ pub fn draw_navmesh_heightmap_gizmos(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use smallvec::smallvec;
+
+    use super::*;
+
+    #[test]
+    fn export_single_triangle() {
+        let mut navmesh = NavMesh::default();
+        navmesh.insert_chunks(
+            IVec2::new(1, 0),
+            NavMeshChunk {
+                is_empty: false,
+                height_map: Grid::new(UVec2::splat(4), 0.0),
+                connections: Grid::new(UVec2::splat(4), 0),
+                triangles: vec![Triangle {
+                    vertices: smallvec![
+                        Vec2::new(0.0, 0.0),
+                        Vec2::new(4.0, 0.0),
+                        Vec2::new(0.0, 3.0)
+                    ],
+                    links: SmallVec::new(),
+                }],
+            },
+        );
+
+        let path = std::env::temp_dir().join(format!("rg_navmesh_{}.obj", std::process::id()));
+        navmesh.export_obj(&path).unwrap();
+        let obj = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(obj, "v 16 0 0\nv 20 0 0\nv 16 3 0\nf 1 2 3\n");
+    }
+}
@@ -26,6 +26,7 @@ pub struct GrassMaterial {
     pub dither_offset: UVec2,
     #[uniform(0)]
     pub fog_height: f32,
+    pub alpha_cutoff: f32,
     #[texture(1)]
     #[sampler(2)]
     pub texture: Handle<Image>,
@@ -42,6 +43,10 @@ impl BillboardMaterial for GrassMaterial {
     fn fragment_shader() -> AssetPath<'static> {
         "shaders/grass.wgsl".into()
     }
+
+    fn alpha_cutoff(&self) -> f32 {
+        self.alpha_cutoff
+    }
 }
 
 #[derive(Debug, Clone, Resource)]
@@ -59,6 +64,7 @@ impl FromWorld for DefaultGrassMaterial {
             noise: asset_server.load("images/noise.png"),
             dither_offset: UVec2::ZERO,
             fog_height: 0.0,
+            alpha_cutoff: 0.5,
         });
 
         Self(material)
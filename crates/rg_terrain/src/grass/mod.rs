@@ -6,7 +6,7 @@ use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
 use rg_core::billboard::{MultiBillboard, MultiBillboardBundle};
 use rg_core::chunk::{Chunk, ChunkPos};
-use rg_worldgen_api::{SharedWorldMaps, WorldSeed};
+use rg_worldgen_api::{WorldReady, WorldSeed};
 
 use self::generator::{generate, GrassResult};
 use self::material::{DefaultGrassMaterial, GrassMaterialPlugin};
@@ -19,7 +19,7 @@ impl Plugin for GrassPlugin {
         app.add_plugins(GrassMaterialPlugin).add_systems(
             Update,
             (
-                schedule_tasks.run_if(resource_exists::<SharedWorldMaps>()),
+                schedule_tasks.in_set(WorldReady),
                 update_tasks.run_if(|q: Query<&GrassTask>| !q.is_empty()),
             ),
         );
@@ -15,6 +15,7 @@ use crate::maps::MapsPlugin;
 pub use crate::maps::{ChunkMaps, SharedChunkMaps};
 use crate::scatter::ScatterPlugins;
 use crate::surface::SurfacePlugin;
+pub use crate::surface::{generate_collider_only, ATTRIBUTE_FLOW};
 pub use crate::tiles::Tile;
 
 pub const MAX_TASKS_IN_FLIGHT: usize = 4;
@@ -2,8 +2,8 @@ use std::sync::Arc;
 
 use bevy::prelude::*;
 use bevy::reflect::{TypePath, TypeUuid};
-use rg_core::chunk::{tile_pos_to_world, CHUNK_TILES};
-use rg_core::grid::Grid;
+use rg_core::chunk::ChunkDimensions;
+use rg_core::grid::{Grid, SampleMode};
 use rg_core::noise::Noise;
 use rg_core::DeserializedResource;
 use rg_worldgen_api::{WorldMaps, WORLD_SCALE};
@@ -20,6 +20,13 @@ pub struct ChunkGenSettings {
     pub terrace_slope: f32,
     pub shore_power: f32,
     pub river_depth: f32,
+    /// Blends face normals with the height field. Disable for flat shading.
+    #[serde(default = "default_smooth_normals")]
+    pub smooth_normals: bool,
+}
+
+fn default_smooth_normals() -> bool {
+    true
 }
 
 impl DeserializedResource for ChunkGenSettings {
@@ -28,43 +35,48 @@ impl DeserializedResource for ChunkGenSettings {
 
 pub fn generate_maps(
     settings: &ChunkGenSettings,
+    dimensions: ChunkDimensions,
     chunk_pos: IVec2,
     world_maps: &WorldMaps,
 ) -> SharedChunkMaps {
     let _span = info_span!("generate_maps").entered();
 
-    let height_map = generate_height_map(settings, chunk_pos, world_maps);
-    let tile_map = generate_tile_map(chunk_pos, world_maps, &height_map);
-    let grass_density_map = generate_grass_density_map(chunk_pos, world_maps, &tile_map);
-    let water_map = generate_water_map(chunk_pos, world_maps);
+    let height_map = generate_height_map(settings, dimensions, chunk_pos, world_maps);
+    let tile_map = generate_tile_map(dimensions, chunk_pos, world_maps, &height_map);
+    let grass_density_map =
+        generate_grass_density_map(dimensions, chunk_pos, world_maps, &tile_map);
+    let water_map = generate_water_map(dimensions, chunk_pos, world_maps);
+    let flow_map = generate_flow_map(dimensions, chunk_pos, world_maps);
 
     SharedChunkMaps(Arc::new(ChunkMaps {
         height_map,
         tile_map,
         grass_density_map,
         water_map,
+        flow_map,
     }))
 }
 
 fn generate_height_map(
     settings: &ChunkGenSettings,
+    dimensions: ChunkDimensions,
     chunk_pos: IVec2,
     world_maps: &WorldMaps,
 ) -> Grid<f32> {
     let _span = info_span!("generate_height_map").entered();
 
     let overscan = 16;
-    let size = UVec2::splat(CHUNK_TILES) + overscan * 2;
+    let size = UVec2::splat(dimensions.tiles) + overscan * 2;
     let origin = -IVec2::splat(overscan as i32);
 
     let blur_map = Grid::from_fn_with_origin(size, origin, |cell| {
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
         let noise = world_maps.noise_maps.island.get(pos)[0];
         5.0 * (2.0 * noise - 1.0).max(0.0) + 1.0
     });
 
     let mut height_map = Grid::from_fn_with_origin(size, origin, |cell| {
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
 
         let mut height = world_maps.height_map.sample(pos / WORLD_SCALE);
         let shore = world_maps.shore_map.sample(pos / WORLD_SCALE);
@@ -88,7 +100,7 @@ fn generate_height_map(
     height_map.variable_gaussian_blur(&blur_map, 1.0, 6.0);
 
     for (cell, height) in height_map.entries_mut() {
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
         let river = world_maps.river_map.sample(pos / WORLD_SCALE);
         *height -= river * settings.river_depth;
     }
@@ -97,19 +109,20 @@ fn generate_height_map(
 }
 
 fn generate_tile_map(
+    dimensions: ChunkDimensions,
     chunk_pos: IVec2,
     world_maps: &WorldMaps,
     height_map: &Grid<f32>,
 ) -> Grid<Tile> {
     let _span = info_span!("generate_tile_map").entered();
 
-    let size = UVec2::splat(CHUNK_TILES);
+    let size = UVec2::splat(dimensions.tiles);
     Grid::from_fn(size, |cell| {
         if height_map[cell] < 0.0 {
             return Tile::Sand;
         }
 
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
         let river = world_maps.river_map.sample(pos / WORLD_SCALE);
 
         if river > 0.1 {
@@ -121,13 +134,14 @@ fn generate_tile_map(
 }
 
 fn generate_grass_density_map(
+    dimensions: ChunkDimensions,
     chunk_pos: IVec2,
     world_maps: &WorldMaps,
     tile_map: &Grid<Tile>,
 ) -> Grid<f32> {
     let _span = info_span!("generate_grass_density_map").entered();
 
-    let size = UVec2::splat(CHUNK_TILES);
+    let size = UVec2::splat(dimensions.tiles);
     Grid::from_fn(size, |cell| {
         if tile_map[cell] != Tile::Grass {
             return 0.0;
@@ -139,23 +153,34 @@ fn generate_grass_density_map(
             }
         }
 
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
         world_maps.noise_maps.grass.get(pos)[0]
     })
 }
 
-fn generate_water_map(chunk_pos: IVec2, world_maps: &WorldMaps) -> Grid<f32> {
+fn generate_water_map(
+    dimensions: ChunkDimensions,
+    chunk_pos: IVec2,
+    world_maps: &WorldMaps,
+) -> Grid<f32> {
     let _span = info_span!("generate_water_map").entered();
 
     let overscan = 1;
-    let size = UVec2::splat(CHUNK_TILES) + overscan * 2;
+    let size = UVec2::splat(dimensions.tiles) + overscan * 2;
     let origin = -IVec2::splat(overscan as i32);
 
     Grid::from_fn_with_origin(size, origin, |cell| {
-        let pos = tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
         let river = world_maps.river_map.sample(pos / WORLD_SCALE);
         let height = world_maps.height_map.sample(pos / WORLD_SCALE);
 
+        let lake = world_maps
+            .lake_map
+            .sample_with(pos / WORLD_SCALE, SampleMode::PropagateNan);
+        if !lake.is_nan() {
+            return lake;
+        }
+
         if height < 0.0 {
             return 0.0;
         }
@@ -167,3 +192,21 @@ fn generate_water_map(chunk_pos: IVec2, world_maps: &WorldMaps) -> Grid<f32> {
         f32::NAN
     })
 }
+
+fn generate_flow_map(
+    dimensions: ChunkDimensions,
+    chunk_pos: IVec2,
+    world_maps: &WorldMaps,
+) -> Grid<Vec2> {
+    let _span = info_span!("generate_flow_map").entered();
+
+    let overscan = 1;
+    let size = UVec2::splat(dimensions.tiles) + overscan * 2;
+    let origin = -IVec2::splat(overscan as i32);
+
+    Grid::from_fn_with_origin(size, origin, |cell| {
+        let pos = dimensions.tile_pos_to_world(IVec2::ZERO, chunk_pos, cell);
+        let world_cell = (pos / WORLD_SCALE).floor().as_ivec2();
+        *world_maps.flow_map.clamped_get(world_cell)
+    })
+}
@@ -5,10 +5,10 @@ use std::sync::Arc;
 use bevy::prelude::*;
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
-use rg_core::chunk::{Chunk, ChunkPos};
+use rg_core::chunk::{Chunk, ChunkDimensions, ChunkPos};
 use rg_core::grid::Grid;
 use rg_core::DeserializedResourcePlugin;
-use rg_worldgen_api::SharedWorldMaps;
+use rg_worldgen_api::{SharedWorldMaps, WorldReady};
 
 use self::generator::generate_maps;
 pub use self::generator::ChunkGenSettings;
@@ -26,7 +26,7 @@ impl Plugin for MapsPlugin {
             Update,
             (
                 schedule_tasks
-                    .run_if(resource_exists::<SharedWorldMaps>())
+                    .in_set(WorldReady)
                     .run_if(resource_exists::<ChunkGenSettings>()),
                 update_tasks.run_if(|q: Query<&MapsTask>| !q.is_empty()),
             ),
@@ -40,6 +40,7 @@ pub struct ChunkMaps {
     pub tile_map: Grid<Tile>,
     pub grass_density_map: Grid<f32>,
     pub water_map: Grid<f32>,
+    pub flow_map: Grid<Vec2>,
 }
 
 #[derive(Debug, Deref, Clone, Component)]
@@ -55,10 +56,12 @@ fn schedule_tasks(
     >,
     q_in_flight: Query<With<MapsTask>>,
     world_maps: Res<SharedWorldMaps>,
+    dimensions: Res<ChunkDimensions>,
     settings: Res<ChunkGenSettings>,
     mut commands: Commands,
 ) {
     let task_pool = AsyncComputeTaskPool::get();
+    let dimensions = *dimensions;
     let settings = *settings;
 
     let mut in_flight = q_in_flight.iter().count();
@@ -71,7 +74,8 @@ fn schedule_tasks(
         in_flight += 1;
 
         let world_maps = world_maps.clone();
-        let task = task_pool.spawn(async move { generate_maps(&settings, chunk_pos, &world_maps) });
+        let task = task_pool
+            .spawn(async move { generate_maps(&settings, dimensions, chunk_pos, &world_maps) });
         commands.entity(chunk_id).insert(MapsTask(task));
     }
 }
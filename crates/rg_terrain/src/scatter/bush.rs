@@ -27,22 +27,24 @@ impl ScatterPrototype for BushPrototype {
         2.0
     }
 
+    fn overlap_radius(&self) -> Option<f32> {
+        Some(0.5)
+    }
+
     fn density(&self, world_maps: &WorldMaps, pos: Vec2) -> f32 {
         let height = world_maps.height_map.sample(pos / WORLD_SCALE);
         if height <= 0.0 {
             return 0.0;
         }
 
-        let biome = world_maps
-            .biome_map
-            .get((pos / WORLD_SCALE).as_ivec2())
-            .copied()
-            .unwrap_or(Biome::Ocean);
+        let biome = world_maps.biome_at(pos);
 
         let p = match biome {
             Biome::Ocean => 0.0,
             Biome::Forest => 0.07,
             Biome::Plains => 0.15,
+            Biome::Tundra => 0.05,
+            Biome::Desert => 0.02,
         };
 
         let shore = world_maps.shore_map.sample(pos / WORLD_SCALE);
@@ -120,6 +122,7 @@ impl FromWorld for BushPrototype {
             noise: asset_server.load("images/noise.png"),
             dither_offset: UVec2::ZERO,
             fog_height: 0.0,
+            alpha_cutoff: 0.5,
         });
 
         Self {
@@ -8,12 +8,13 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg32;
+use rg_core::billboard::{BillboardInstance, MultiBillboard, MultiBillboardBundle};
 use rg_core::chunk::{
     chunk_pos_to_world, Chunk, ChunkFullyLoaded, ChunkPos, ChunkSpawnCenter, WorldOrigin,
     CHUNK_SIZE,
 };
-use rg_core::PoissonDiscSampling;
-use rg_worldgen_api::{SharedWorldMaps, WorldMaps, WorldSeed};
+use rg_core::{CollisionLayers, PoissonDiscSampling};
+use rg_worldgen_api::{Biome, SharedWorldMaps, WorldMaps, WorldReady, WorldSeed};
 
 use self::bush::BushPrototype;
 use self::tree::TreePrototype;
@@ -41,12 +42,34 @@ pub trait ScatterPrototype: Resource + FromWorld + 'static {
         64
     }
 
+    /// Points outside of these biomes are rejected. Empty means any biome.
+    fn allowed_biomes(&self) -> &[Biome] {
+        &[]
+    }
+
     fn density(&self, world_maps: &WorldMaps, pos: Vec2) -> f32 {
         let _ = (world_maps, pos);
         1.0
     }
 
+    /// Points whose prop would intersect an existing non-walkable collider
+    /// within this radius are rejected.
+    fn overlap_radius(&self) -> Option<f32> {
+        None
+    }
+
     fn spawn<R: Rng>(&self, rng: &mut R, commands: &mut Commands, pos: Vec3) -> Entity;
+
+    /// When this returns an instance, the point is batched into a per-chunk
+    /// `MultiBillboard` instead of being passed to `spawn`.
+    fn billboard_instance<R: Rng>(&self, rng: &mut R, pos: Vec3) -> Option<BillboardInstance> {
+        let _ = (rng, pos);
+        None
+    }
+
+    fn billboard_anchor(&self) -> Vec2 {
+        Vec2::new(0.5, 1.0)
+    }
 }
 
 pub struct ScatterPlugin<T: ScatterPrototype>(PhantomData<T>);
@@ -60,10 +83,7 @@ impl<T: ScatterPrototype> Default for ScatterPlugin<T> {
 impl<T: ScatterPrototype> Plugin for ScatterPlugin<T> {
     fn build(&self, app: &mut App) {
         T::build_app(app);
-        app.add_systems(
-            Update,
-            scatter::<T>.run_if(resource_exists::<SharedWorldMaps>()),
-        );
+        app.add_systems(Update, scatter::<T>.in_set(WorldReady));
     }
 
     fn finish(&self, app: &mut App) {
@@ -81,7 +101,9 @@ fn scatter<T: ScatterPrototype>(
     world_maps: Res<SharedWorldMaps>,
     prototype: Res<T>,
     physics_context: Res<RapierContext>,
+    q_collision_groups: Query<&CollisionGroups>,
     spawn_center: Res<ChunkSpawnCenter>,
+    mut multi_billboards: ResMut<Assets<MultiBillboard>>,
     mut commands: Commands,
 ) {
     let spawn_center = spawn_center.0;
@@ -110,9 +132,16 @@ fn scatter<T: ScatterPrototype>(
     let points = sampling.points;
 
     let mut children = Vec::new();
+    let mut instances = Vec::new();
 
     for pos in points {
         let global_pos = chunk_pos_to_world(IVec2::ZERO, chunk_pos.0) + pos;
+
+        let biome_at = || world_maps.biome_at(global_pos);
+        if !biome_allowed(prototype.allowed_biomes(), biome_at) {
+            continue;
+        }
+
         let density = prototype.density(&world_maps, global_pos);
         if !rng.gen_bool(density as f64) {
             continue;
@@ -130,13 +159,179 @@ fn scatter<T: ScatterPrototype>(
             continue;
         };
 
-        let z = 1000.0 - toi;
-        let entity = prototype.spawn(&mut rng, &mut commands, pos.extend(z));
+        if let Some(radius) = prototype.overlap_radius() {
+            let center = relative_pos.extend(1000.0 - toi + radius);
+            if overlaps_obstacles(&physics_context, &q_collision_groups, center, radius) {
+                continue;
+            }
+        }
+
+        let pos = pos.extend(1000.0 - toi);
+
+        if let Some(instance) = prototype.billboard_instance(&mut rng, pos) {
+            instances.push(instance);
+            continue;
+        }
+
+        let entity = prototype.spawn(&mut rng, &mut commands, pos);
         children.push(entity);
     }
 
+    if !instances.is_empty() {
+        let anchor = prototype.billboard_anchor();
+        children.push(spawn_multi_billboard(
+            &mut commands,
+            &mut multi_billboards,
+            instances,
+            anchor,
+        ));
+    }
+
     commands
         .entity(chunk_id)
         .insert((ChunkScattered::<T>(PhantomData), ChunkFullyLoaded))
         .push_children(&children);
 }
+
+fn spawn_multi_billboard(
+    commands: &mut Commands,
+    multi_billboards: &mut Assets<MultiBillboard>,
+    instances: Vec<BillboardInstance>,
+    anchor: Vec2,
+) -> Entity {
+    let multi_billboard = multi_billboards.add(MultiBillboard {
+        instances: instances.into(),
+        anchor,
+    });
+
+    commands
+        .spawn(MultiBillboardBundle {
+            multi_billboard,
+            ..default()
+        })
+        .id()
+}
+
+fn overlaps_obstacles(
+    physics_context: &RapierContext,
+    q_collision_groups: &Query<&CollisionGroups>,
+    center: Vec3,
+    radius: f32,
+) -> bool {
+    let predicate = |entity| is_obstacle(q_collision_groups.get(entity).ok());
+
+    physics_context
+        .intersection_with_shape(
+            center,
+            Quat::IDENTITY,
+            &Collider::ball(radius),
+            QueryFilter::new().predicate(&predicate),
+        )
+        .is_some()
+}
+
+/// Only looks up the biome when the prototype restricts biomes.
+fn biome_allowed(allowed_biomes: &[Biome], biome_at: impl FnOnce() -> Biome) -> bool {
+    allowed_biomes.is_empty() || allowed_biomes.contains(&biome_at())
+}
+
+/// Everything but walkable colliders blocks scattering, including colliders
+/// without collision groups.
+fn is_obstacle(groups: Option<&CollisionGroups>) -> bool {
+    let walkable = Group::from(CollisionLayers::WALKABLE);
+    groups.map_or(true, |groups| !groups.memberships.contains(walkable))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::CommandQueue;
+
+    use super::*;
+
+    #[derive(Default, Resource)]
+    struct FlowerPrototype;
+
+    impl ScatterPrototype for FlowerPrototype {
+        const SEED: u64 = 0;
+
+        fn poisson_disc_min_radius(&self) -> f32 {
+            1.0
+        }
+
+        fn spawn<R: Rng>(&self, _rng: &mut R, commands: &mut Commands, pos: Vec3) -> Entity {
+            commands
+                .spawn(TransformBundle::from(Transform::from_translation(pos)))
+                .id()
+        }
+
+        fn billboard_instance<R: Rng>(&self, rng: &mut R, pos: Vec3) -> Option<BillboardInstance> {
+            Some(BillboardInstance {
+                pos,
+                normal: Vec3::Z,
+                size: Vec2::ONE,
+                color: Vec3::ONE,
+                random: rng.gen(),
+            })
+        }
+    }
+
+    #[test]
+    fn points_become_billboard_instances() {
+        const N: usize = 17;
+
+        let mut world = World::new();
+        world.init_resource::<Assets<MultiBillboard>>();
+
+        let prototype = FlowerPrototype;
+        let mut rng = Pcg32::seed_from_u64(0);
+        let instances = (0..N)
+            .filter_map(|i| prototype.billboard_instance(&mut rng, Vec3::X * i as f32))
+            .collect::<Vec<_>>();
+
+        let mut queue = CommandQueue::default();
+        let entity = world.resource_scope(|world, mut assets: Mut<Assets<MultiBillboard>>| {
+            let mut commands = Commands::new(&mut queue, world);
+            let anchor = prototype.billboard_anchor();
+            spawn_multi_billboard(&mut commands, &mut assets, instances, anchor)
+        });
+        queue.apply(&mut world);
+
+        let handle = world.get::<Handle<MultiBillboard>>(entity).unwrap();
+        let assets = world.resource::<Assets<MultiBillboard>>();
+        assert_eq!(assets.get(handle).unwrap().instances.len(), N);
+    }
+
+    fn num_allowed_points(allowed_biomes: &[Biome], biome_at: fn() -> Biome) -> usize {
+        let sampling =
+            PoissonDiscSampling::new_tileable(0, IVec2::ZERO, Vec2::splat(CHUNK_SIZE), 1.0, 64);
+        let points = sampling.points;
+        assert!(!points.is_empty());
+
+        points
+            .iter()
+            .filter(|_| biome_allowed(allowed_biomes, biome_at))
+            .count()
+    }
+
+    #[test]
+    fn forest_only_rejects_plains_chunk() {
+        assert_eq!(num_allowed_points(&[Biome::Forest], || Biome::Plains), 0);
+        assert!(num_allowed_points(&[Biome::Forest], || Biome::Forest) > 0);
+    }
+
+    #[test]
+    fn no_biome_restriction_skips_lookup() {
+        let num_points = num_allowed_points(&[], || panic!("biome looked up"));
+        assert!(num_points > 0);
+    }
+
+    #[test]
+    fn only_walkable_colliders_are_not_obstacles() {
+        assert!(!is_obstacle(Some(&CollisionLayers::STATIC_WALKABLE_GROUP)));
+
+        assert!(is_obstacle(None));
+        assert!(is_obstacle(Some(&CollisionLayers::STATIC_GROUP)));
+        assert!(is_obstacle(Some(&CollisionLayers::DYNAMIC_GROUP)));
+        assert!(is_obstacle(Some(&CollisionLayers::CHARACTER_GROUP)));
+    }
+}
@@ -35,22 +35,24 @@ impl ScatterPrototype for TreePrototype {
         4.0
     }
 
+    fn allowed_biomes(&self) -> &[Biome] {
+        &[Biome::Forest, Biome::Plains]
+    }
+
     fn density(&self, world_maps: &WorldMaps, pos: Vec2) -> f32 {
         let height = world_maps.height_map.sample(pos / WORLD_SCALE);
         if height <= 0.0 {
             return 0.0;
         }
 
-        let biome = world_maps
-            .biome_map
-            .get((pos / WORLD_SCALE).as_ivec2())
-            .copied()
-            .unwrap_or(Biome::Ocean);
+        let biome = world_maps.biome_at(pos);
 
         let p = match biome {
             Biome::Ocean => 0.0,
             Biome::Forest => 1.0,
             Biome::Plains => 0.1,
+            Biome::Tundra => 0.0,
+            Biome::Desert => 0.0,
         };
 
         let shore = world_maps.shore_map.sample(pos / WORLD_SCALE);
@@ -146,6 +148,7 @@ impl FromWorld for TreePrototype {
             noise: asset_server.load("images/noise.png"),
             dither_offset: UVec2::ZERO,
             fog_height: 0.0,
+            alpha_cutoff: 0.5,
         });
 
         Self {
@@ -164,6 +167,7 @@ pub struct LeavesMaterial {
     pub dither_offset: UVec2,
     #[uniform(0)]
     pub fog_height: f32,
+    pub alpha_cutoff: f32,
     #[texture(1)]
     #[sampler(2)]
     pub texture: Handle<Image>,
@@ -180,6 +184,10 @@ impl BillboardMaterial for LeavesMaterial {
     fn fragment_shader() -> AssetPath<'static> {
         "shaders/leaves.wgsl".into()
     }
+
+    fn alpha_cutoff(&self) -> f32 {
+        self.alpha_cutoff
+    }
 }
 
 fn update_globals(
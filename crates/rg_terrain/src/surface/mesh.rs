@@ -1,31 +1,51 @@
 use bevy::math::{ivec2, vec2, vec3, Vec3Swizzles};
 use bevy::prelude::*;
-use bevy::render::render_resource::PrimitiveTopology;
+use bevy::render::mesh::MeshVertexAttribute;
+use bevy::render::render_resource::{PrimitiveTopology, VertexFormat};
 use bevy::utils::HashMap;
 use bevy_rapier3d::prelude::*;
-use rg_core::chunk::{CHUNK_TILES, TILE_SIZE};
+use rg_core::chunk::ChunkDimensions;
 use rg_core::grid::Grid;
 
 const VERTICES_CAP: usize = 128 * 1024;
 const INDICES_CAP: usize = 128 * 1024;
 
+/// Vertex heights are snapped to multiples of `1 / HEIGHT_QUANTIZATION`.
+const HEIGHT_QUANTIZATION: f32 = 25.0;
+
+pub const ATTRIBUTE_FLOW: MeshVertexAttribute =
+    MeshVertexAttribute::new("Flow", 988540917, VertexFormat::Float32x2);
+
 pub struct MeshResult {
     pub terrain_mesh: Mesh,
     pub terrain_collider: Collider,
     pub water_mesh: Mesh,
 }
 
-pub fn generate_mesh(height_map: &Grid<f32>, river_map: &Grid<f32>) -> MeshResult {
+pub fn generate_mesh(
+    dimensions: ChunkDimensions,
+    height_map: &Grid<f32>,
+    river_map: &Grid<f32>,
+    flow_map: &Grid<Vec2>,
+    smooth_normals: bool,
+) -> MeshResult {
     let _span = info_span!("generate_mesh").entered();
-    MeshGenerator::new(height_map, river_map).generate()
+    MeshGenerator::new(dimensions, height_map).generate(river_map, flow_map, smooth_normals)
+}
+
+/// Generates only the terrain collider, skipping the render-only passes.
+pub fn generate_collider_only(dimensions: ChunkDimensions, height_map: &Grid<f32>) -> Collider {
+    let _span = info_span!("generate_collider_only").entered();
+    MeshGenerator::new(dimensions, height_map).generate_collider_only()
 }
 
 struct MeshGenerator<'a> {
+    dimensions: ChunkDimensions,
     height_map: &'a Grid<f32>,
-    river_map: &'a Grid<f32>,
     positions: Vec<Vec3>,
     normals: Vec<Vec3>,
     colors: Vec<Vec4>,
+    flows: Vec<Vec2>,
     indices: Vec<u32>,
     height_step: f32,
     cell: IVec2,
@@ -45,21 +65,31 @@ struct MeshGenerator<'a> {
 }
 
 impl MeshGenerator<'_> {
-    fn new<'a>(height_map: &'a Grid<f32>, river_map: &'a Grid<f32>) -> MeshGenerator<'a> {
+    fn new(dimensions: ChunkDimensions, height_map: &Grid<f32>) -> MeshGenerator<'_> {
+        assert!(
+            height_map.contains_cell(IVec2::ZERO)
+                && height_map.contains_cell(IVec2::splat(dimensions.tiles as i32)),
+            "height map doesn't cover a chunk of {} tiles",
+            dimensions.tiles,
+        );
+
+        let cells_size = UVec2::splat(dimensions.tiles + 1);
+
         MeshGenerator {
+            dimensions,
             height_map,
-            river_map,
             positions: Vec::with_capacity(VERTICES_CAP),
             normals: Vec::with_capacity(VERTICES_CAP),
             colors: Vec::with_capacity(VERTICES_CAP),
+            flows: Vec::new(),
             indices: Vec::with_capacity(INDICES_CAP),
             height_step: 0.25,
             cell: IVec2::ZERO,
             cell_first_vertex: 0,
             cell_first_index: 0,
-            cell_indices: Grid::new(UVec2::splat(CHUNK_TILES + 1), [0, 0]),
-            cell_vertices: Grid::new(UVec2::splat(CHUNK_TILES + 1), [0, 0]),
-            cell_walls: Grid::new(UVec2::splat(CHUNK_TILES + 1), Vec::new()),
+            cell_indices: Grid::new(cells_size, [0, 0]),
+            cell_vertices: Grid::new(cells_size, [0, 0]),
+            cell_walls: Grid::new(cells_size, Vec::new()),
             height: 0.0,
             up_height: 0.0,
             mask: 0,
@@ -71,10 +101,19 @@ impl MeshGenerator<'_> {
         }
     }
 
-    fn generate(mut self) -> MeshResult {
+    fn generate(
+        mut self,
+        river_map: &Grid<f32>,
+        flow_map: &Grid<Vec2>,
+        smooth_normals: bool,
+    ) -> MeshResult {
         self.generate_cells();
         self.compute_colors();
-        self.snap_normals();
+
+        if smooth_normals {
+            self.snap_normals();
+        }
+
         self.cleanup_triangles();
         self.remove_rejected_triangles();
         self.deduplicate();
@@ -83,9 +122,10 @@ impl MeshGenerator<'_> {
         let terrain_collider = self.create_collider();
         let terrain_mesh = self.create_mesh(true);
 
-        self.generate_water_mesh();
+        self.generate_water_mesh(river_map, flow_map);
 
-        let water_mesh = self.create_mesh(false);
+        let mut water_mesh = self.create_mesh(false);
+        water_mesh.insert_attribute(ATTRIBUTE_FLOW, self.flows.clone());
 
         MeshResult {
             terrain_mesh,
@@ -94,11 +134,27 @@ impl MeshGenerator<'_> {
         }
     }
 
+    fn generate_collider_only(mut self) -> Collider {
+        self.generate_cells();
+        self.cleanup_triangles();
+        self.remove_rejected_triangles();
+        self.deduplicate();
+        self.apply_scale();
+        self.create_collider()
+    }
+
     fn generate_cells(&mut self) {
         let _span = info_span!("generate_cells").entered();
 
-        for y in 0..CHUNK_TILES as i32 {
-            for x in 0..CHUNK_TILES as i32 {
+        if let Some(&height) = self.height_map.is_uniform() {
+            self.generate_flat_cells(height);
+            return;
+        }
+
+        let tiles = self.dimensions.tiles as i32;
+
+        for y in 0..tiles {
+            for x in 0..tiles {
                 self.cell = ivec2(x, y);
                 self.cell_first_vertex = self.positions.len();
                 self.cell_first_index = self.indices.len();
@@ -119,6 +175,27 @@ impl MeshGenerator<'_> {
         }
     }
 
+    fn generate_flat_cells(&mut self, height: f32) {
+        let size = self.dimensions.tiles as f32;
+        let height = quantize_height(height);
+
+        self.cell = IVec2::ZERO;
+        self.cell_first_vertex = self.positions.len();
+        self.cell_first_index = self.indices.len();
+
+        self.ms_quad_3d(
+            vec3(0.0, 0.0, height),
+            vec3(size, 0.0, height),
+            vec3(size, size, height),
+            vec3(0.0, size, height),
+        );
+
+        self.compute_cell_normals();
+
+        self.cell_indices[self.cell] = [self.cell_first_index, self.indices.len()];
+        self.cell_vertices[self.cell] = [self.cell_first_vertex, self.positions.len()];
+    }
+
     fn generate_cell(&mut self, pos: IVec2) {
         let height_tl = self.get_quantized_height(pos + ivec2(0, 0));
         let height_tr = self.get_quantized_height(pos + ivec2(1, 0));
@@ -161,7 +238,7 @@ impl MeshGenerator<'_> {
         }
     }
 
-    fn generate_water_mesh(&mut self) {
+    fn generate_water_mesh(&mut self, river_map: &Grid<f32>, flow_map: &Grid<Vec2>) {
         let _span = info_span!("generate_water_mesh").entered();
 
         self.positions.clear();
@@ -169,14 +246,16 @@ impl MeshGenerator<'_> {
         self.colors.clear();
         self.indices.clear();
 
-        for y in 0..CHUNK_TILES as i32 {
-            for x in 0..CHUNK_TILES as i32 {
+        let tiles = self.dimensions.tiles as i32;
+
+        for y in 0..tiles {
+            for x in 0..tiles {
                 let pos = IVec2::new(x, y);
 
-                let height_tl = self.river_map[pos + ivec2(0, 0)];
-                let height_tr = self.river_map[pos + ivec2(1, 0)];
-                let height_br = self.river_map[pos + ivec2(1, 1)];
-                let height_bl = self.river_map[pos + ivec2(0, 1)];
+                let height_tl = river_map[pos + ivec2(0, 0)];
+                let height_tr = river_map[pos + ivec2(1, 0)];
+                let height_br = river_map[pos + ivec2(1, 1)];
+                let height_bl = river_map[pos + ivec2(0, 1)];
 
                 if height_tl.is_nan()
                     || height_tr.is_nan()
@@ -197,13 +276,23 @@ impl MeshGenerator<'_> {
             }
         }
 
+        self.flows = self
+            .positions
+            .iter()
+            .map(|pos| *flow_map.clamped_get(pos.xy().round().as_ivec2()))
+            .collect();
+
         self.apply_scale();
         self.compute_normals(0, self.indices.len());
     }
 
     fn snap_cell_vertices(&mut self) {
         for pos in &mut self.positions[self.cell_first_vertex..] {
-            let height = self.height_map.sample(pos.xy());
+            let height = if cfg!(feature = "bicubic_heights") {
+                self.height_map.sample_bicubic(pos.xy())
+            } else {
+                self.height_map.sample(pos.xy())
+            };
             let grad = self.height_map.sample_grad(pos.xy());
 
             let alpha = (grad.length() * 3.0).clamp(0.0, 1.0).powf(3.0);
@@ -230,10 +319,15 @@ impl MeshGenerator<'_> {
         }
 
         for pos in &mut self.positions[self.cell_first_vertex..] {
-            pos.z = (pos.z * 25.0).round() / 25.0;
+            pos.z = quantize_height(pos.z);
         }
     }
 
+    /// Vertex color layout:
+    /// - x: proximity to the edge of a wall
+    /// - y: ambient occlusion by the surrounding terrain
+    /// - z: reserved for biome tint
+    /// - w: reserved for wetness
     fn compute_colors(&mut self) {
         let _span = info_span!("compute_colors").entered();
 
@@ -242,6 +336,8 @@ impl MeshGenerator<'_> {
         let colors = self.colors.iter_mut();
 
         for ((&pos, &normal), color) in positions.zip(normals).zip(colors) {
+            color.y = self.compute_occlusion(pos);
+
             if normal.z.abs() < 0.1 {
                 continue;
             }
@@ -277,6 +373,23 @@ impl MeshGenerator<'_> {
         }
     }
 
+    fn compute_occlusion(&self, pos: Vec3) -> f32 {
+        const SAMPLES: usize = 8;
+        const RADIUS: f32 = 1.5;
+        const MAX_HEIGHT: f32 = 2.0;
+
+        let mut occlusion = 0.0;
+
+        for i in 0..SAMPLES {
+            let angle = (i as f32) / (SAMPLES as f32) * std::f32::consts::TAU;
+            let sample_pos = pos.xy() + Vec2::from_angle(angle) * RADIUS;
+            let height = self.height_map.sample(sample_pos);
+            occlusion += ((height - pos.z) / MAX_HEIGHT).clamp(0.0, 1.0);
+        }
+
+        occlusion / (SAMPLES as f32)
+    }
+
     fn cleanup_triangles(&mut self) {
         let _span = info_span!("cleanup_triangles").entered();
 
@@ -315,13 +428,15 @@ impl MeshGenerator<'_> {
     fn snap_normals(&mut self) {
         let _span = info_span!("snap_normals").entered();
 
+        let tile_size = self.dimensions.tile_size;
+
         for (pos, normal) in self.positions.iter_mut().zip(&mut self.normals) {
             if normal.z.abs() < 0.1 {
                 continue;
             }
 
             let grad = self.height_map.sample_grad(pos.xy());
-            let target_normal = vec3(-grad.x, -grad.y, 1.0 * TILE_SIZE).normalize();
+            let target_normal = vec3(-grad.x, -grad.y, 1.0 * tile_size).normalize();
             *normal = (*normal * 0.7 + target_normal * 0.3).normalize();
         }
     }
@@ -372,9 +487,11 @@ impl MeshGenerator<'_> {
     fn apply_scale(&mut self) {
         let _span = info_span!("apply_scale").entered();
 
+        let tile_size = self.dimensions.tile_size;
+
         for pos in &mut self.positions {
-            pos.x *= TILE_SIZE;
-            pos.y *= TILE_SIZE;
+            pos.x *= tile_size;
+            pos.y *= tile_size;
         }
     }
 
@@ -836,3 +953,149 @@ impl MeshGenerator<'_> {
         (self.height_map[pos] / self.height_step).floor() * self.height_step
     }
 }
+
+fn quantize_height(height: f32) -> f32 {
+    (height * HEIGHT_QUANTIZATION).round() / HEIGHT_QUANTIZATION
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dimensions(tiles: u32) -> ChunkDimensions {
+        ChunkDimensions {
+            tiles,
+            tile_size: 0.5,
+        }
+    }
+
+    #[test]
+    fn grids_are_sized_from_dimensions() {
+        let height_map = Grid::new(UVec2::splat(9), 0.0);
+        let generator = MeshGenerator::new(dimensions(8), &height_map);
+
+        assert_eq!(generator.cell_indices.size(), UVec2::splat(9));
+        assert_eq!(generator.cell_vertices.size(), UVec2::splat(9));
+        assert_eq!(generator.cell_walls.size(), UVec2::splat(9));
+    }
+
+    #[test]
+    fn flat_cells_use_quantized_height() {
+        let height_map = Grid::new(UVec2::splat(9), 1.013);
+        let mut generator = MeshGenerator::new(dimensions(8), &height_map);
+        generator.generate_cells();
+
+        assert!(!generator.positions.is_empty());
+        for pos in &generator.positions {
+            assert_eq!(pos.z, quantize_height(1.013));
+        }
+    }
+
+    #[test]
+    fn occlusion_is_higher_in_concave_regions() {
+        let flat = Grid::new(UVec2::splat(9), 0.0);
+        let bowl = Grid::from_fn(UVec2::splat(9), |cell| {
+            (cell - IVec2::splat(4)).as_vec2().length_squared() * 0.2
+        });
+
+        let center = vec3(4.0, 4.0, 0.0);
+        let flat_ao = MeshGenerator::new(dimensions(8), &flat).compute_occlusion(center);
+        let bowl_ao = MeshGenerator::new(dimensions(8), &bowl).compute_occlusion(center);
+
+        assert_eq!(flat_ao, 0.0);
+        assert!(bowl_ao > flat_ao);
+    }
+
+    fn slopes() -> Grid<f32> {
+        Grid::from_fn(UVec2::splat(9), |cell| {
+            (cell.x as f32 * 0.45).sin() * 2.0 + cell.y as f32 * 0.3
+        })
+    }
+
+    fn has_flat_faces(smooth_normals: bool) -> bool {
+        use bevy::render::mesh::{Indices, VertexAttributeValues};
+
+        let height_map = slopes();
+        let river_map = Grid::new(UVec2::splat(9), f32::NAN);
+        let flow_map = Grid::new(UVec2::splat(9), Vec2::ZERO);
+
+        let res = generate_mesh(
+            dimensions(8),
+            &height_map,
+            &river_map,
+            &flow_map,
+            smooth_normals,
+        );
+
+        let Some(VertexAttributeValues::Float32x3(normals)) =
+            res.terrain_mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        else {
+            panic!("missing normals");
+        };
+        let Some(Indices::U32(indices)) = res.terrain_mesh.indices() else {
+            panic!("missing indices");
+        };
+
+        assert!(!indices.is_empty());
+        indices.chunks_exact(3).all(|triangle| {
+            let normal = normals[triangle[0] as usize];
+            triangle.iter().all(|&i| normals[i as usize] == normal)
+        })
+    }
+
+    #[test]
+    fn flat_shading_has_per_face_normals() {
+        assert!(has_flat_faces(false));
+        assert!(!has_flat_faces(true));
+    }
+
+    #[test]
+    fn collider_only_matches_full_collider() {
+        let height_map = slopes();
+        let river_map = Grid::new(UVec2::splat(9), f32::NAN);
+        let flow_map = Grid::new(UVec2::splat(9), Vec2::ZERO);
+
+        let full = generate_mesh(dimensions(8), &height_map, &river_map, &flow_map, false);
+        let collider = generate_collider_only(dimensions(8), &height_map);
+
+        let num_triangles = |collider: &Collider| collider.as_trimesh().unwrap().num_triangles();
+        assert!(num_triangles(&full.terrain_collider) > 0);
+        assert_eq!(
+            num_triangles(&collider),
+            num_triangles(&full.terrain_collider)
+        );
+    }
+
+    #[test]
+    fn flow_matches_water_vertices() {
+        use bevy::render::mesh::VertexAttributeValues;
+
+        let height_map = slopes();
+        let mut river_map = Grid::new(UVec2::splat(9), f32::NAN);
+        for (cell, height) in river_map.entries_mut() {
+            if cell.x < 4 {
+                *height = 0.5;
+            }
+        }
+        let flow_map = Grid::new(UVec2::splat(9), Vec2::X);
+
+        let res = generate_mesh(dimensions(8), &height_map, &river_map, &flow_map, false);
+        let water_mesh = res.water_mesh;
+
+        let Some(VertexAttributeValues::Float32x2(flows)) = water_mesh.attribute(ATTRIBUTE_FLOW)
+        else {
+            panic!("missing flow");
+        };
+
+        assert!(water_mesh.count_vertices() > 0);
+        assert_eq!(flows.len(), water_mesh.count_vertices());
+        assert!(flows.iter().all(|&flow| flow == [1.0, 0.0]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejects_too_small_height_map() {
+        let height_map = Grid::new(UVec2::splat(9), 0.0);
+        MeshGenerator::new(dimensions(16), &height_map);
+    }
+}
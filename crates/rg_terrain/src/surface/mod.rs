@@ -4,12 +4,14 @@ mod mesh;
 use bevy::prelude::*;
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
-use rg_core::chunk::Chunk;
+use rg_core::chunk::{Chunk, ChunkDimensions};
 use rg_core::CollisionLayers;
 use rg_navigation_api::NavMeshAffector;
 
 use self::material::{SurfaceMaterials, SurfaceMaterialsPlugin};
+pub use self::mesh::{generate_collider_only, ATTRIBUTE_FLOW};
 use self::mesh::{generate_mesh, MeshResult};
+use crate::maps::ChunkGenSettings;
 use crate::{SharedChunkMaps, MAX_TASKS_IN_FLIGHT};
 
 pub struct SurfacePlugin;
@@ -19,7 +21,7 @@ impl Plugin for SurfacePlugin {
         app.add_plugins(SurfaceMaterialsPlugin).add_systems(
             Update,
             (
-                schedule_tasks,
+                schedule_tasks.run_if(resource_exists::<ChunkGenSettings>()),
                 update_tasks.run_if(|q: Query<&SurfaceTask>| !q.is_empty()),
             ),
         );
@@ -35,8 +37,12 @@ fn schedule_tasks(
         (With<Chunk>, Without<Handle<Mesh>>, Without<SurfaceTask>),
     >,
     q_in_flight: Query<(), With<SurfaceTask>>,
+    dimensions: Res<ChunkDimensions>,
+    settings: Res<ChunkGenSettings>,
     mut commands: Commands,
 ) {
+    let dimensions = *dimensions;
+    let smooth_normals = settings.smooth_normals;
     let task_pool = AsyncComputeTaskPool::get();
 
     let mut in_flight = q_in_flight.iter().count();
@@ -49,8 +55,15 @@ fn schedule_tasks(
         in_flight += 1;
 
         let chunk_maps = chunk_maps.clone();
-        let task = task_pool
-            .spawn(async move { generate_mesh(&chunk_maps.height_map, &chunk_maps.water_map) });
+        let task = task_pool.spawn(async move {
+            generate_mesh(
+                dimensions,
+                &chunk_maps.height_map,
+                &chunk_maps.water_map,
+                &chunk_maps.flow_map,
+                smooth_normals,
+            )
+        });
         commands.entity(chunk_id).insert(SurfaceTask(task));
     }
 }
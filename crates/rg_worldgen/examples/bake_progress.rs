@@ -0,0 +1,20 @@
+//! Regenerates `src/progress.bin` from `default.worldgen.ron`. Must be rerun
+//! whenever `WorldgenStage` or the tasks of any stage change:
+//! `cargo run -p rg_worldgen --release --example bake_progress`
+
+use std::path::Path;
+
+use rg_worldgen::bake_progress;
+use rg_worldgen_api::WorldgenSettings;
+
+fn main() -> anyhow::Result<()> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let settings = std::fs::read(root.join("../../assets/default.worldgen.ron"))?;
+    let settings = ron::de::from_bytes::<WorldgenSettings>(&settings)?;
+
+    let data = bake_progress(0, &settings);
+    anyhow::ensure!(!data.is_empty(), "no progress samples were recorded");
+
+    std::fs::write(root.join("src/progress.bin"), data)?;
+    Ok(())
+}
@@ -2,28 +2,110 @@ use bevy::prelude::*;
 use rg_core::grid::Grid;
 use rg_core::noise::Noise;
 use rg_core::progress::ProgressStage;
-use rg_worldgen_api::{Biome, NoiseMaps};
+use rg_worldgen_api::{Biome, NoiseMaps, TemperatureSettings};
 
 pub fn generate_biome_map(
     progress: &mut ProgressStage,
+    settings: &TemperatureSettings,
     noise_maps: &NoiseMaps,
     height_map: &Grid<f32>,
+    temperature_map: &Grid<f32>,
+    sea_level: f32,
 ) -> Grid<Biome> {
     let _scope = info_span!("generate_biome_map").entered();
 
     let size = height_map.size();
     progress.task(|| {
         Grid::par_from_fn(size, |cell| {
-            if height_map[cell] < 0.0 {
-                return Biome::Ocean;
-            }
-
-            let noise = noise_maps.biomes.get(cell.as_vec2())[0];
-            if noise > 0.5 {
-                Biome::Forest
-            } else {
-                Biome::Plains
-            }
+            let moisture = noise_maps.biomes.get(cell.as_vec2())[0];
+            classify_biome(
+                settings,
+                height_map[cell],
+                moisture,
+                temperature_map[cell],
+                sea_level,
+            )
         })
     })
 }
+
+/// Pure biome decision for a single point. The tundra and desert thresholds
+/// come from `TemperatureSettings`, since they are tuned in the worldgen
+/// settings rather than hard-coded.
+pub fn classify_biome(
+    settings: &TemperatureSettings,
+    height: f32,
+    moisture: f32,
+    temperature: f32,
+    sea_level: f32,
+) -> Biome {
+    if height < sea_level {
+        return Biome::Ocean;
+    }
+
+    if temperature < settings.tundra_below {
+        return Biome::Tundra;
+    }
+
+    if temperature > settings.desert_above {
+        return Biome::Desert;
+    }
+
+    if moisture > 0.5 {
+        Biome::Forest
+    } else {
+        Biome::Plains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: TemperatureSettings = TemperatureSettings {
+        equator_temperature: 28.0,
+        pole_temperature: 0.0,
+        lapse_rate: 0.25,
+        noise_strength: 3.0,
+        tundra_below: 2.0,
+        desert_above: 26.0,
+    };
+
+    #[test]
+    fn below_sea_level_is_ocean() {
+        assert_eq!(
+            classify_biome(&SETTINGS, -1.0, 0.9, 15.0, 0.0),
+            Biome::Ocean
+        );
+        assert_eq!(classify_biome(&SETTINGS, 0.5, 0.9, 15.0, 1.0), Biome::Ocean);
+        assert_ne!(classify_biome(&SETTINGS, 0.5, 0.9, 15.0, 0.0), Biome::Ocean);
+    }
+
+    #[test]
+    fn high_dry_cold_is_tundra() {
+        assert_eq!(
+            classify_biome(&SETTINGS, 40.0, 0.1, -5.0, 0.0),
+            Biome::Tundra
+        );
+    }
+
+    #[test]
+    fn low_dry_hot_is_desert() {
+        assert_eq!(
+            classify_biome(&SETTINGS, 2.0, 0.1, 30.0, 0.0),
+            Biome::Desert
+        );
+    }
+
+    #[test]
+    fn temperate_splits_on_moisture() {
+        assert_eq!(
+            classify_biome(&SETTINGS, 5.0, 0.8, 15.0, 0.0),
+            Biome::Forest
+        );
+        assert_eq!(
+            classify_biome(&SETTINGS, 5.0, 0.2, 15.0, 0.0),
+            Biome::Plains
+        );
+    }
+}
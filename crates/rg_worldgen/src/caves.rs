@@ -0,0 +1,128 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::Rng;
+use rg_core::grid::Grid;
+use rg_core::progress::ProgressStage;
+use rg_worldgen_api::CavesSettings;
+
+const BIRTH: [u8; 4] = [5, 6, 7, 8];
+const SURVIVE: [u8; 5] = [4, 5, 6, 7, 8];
+
+pub fn generate_cave_map<R: Rng>(
+    rng: &mut R,
+    progress: &mut ProgressStage,
+    settings: &CavesSettings,
+    height_map: &Grid<f32>,
+) -> Grid<bool> {
+    let _scope = info_span!("generate_cave_map").entered();
+
+    let is_solid = |cell: IVec2| height_map[cell] > settings.min_height;
+
+    let mut cave_map = progress.task(|| {
+        Grid::from_fn(height_map.size(), |cell| {
+            is_solid(cell) && rng.gen_bool(settings.fill_probability as f64)
+        })
+    });
+
+    for _ in 0..settings.steps {
+        cave_map = progress.task(|| {
+            let mut grid = cave_map.cellular_automata_step(&BIRTH, &SURVIVE);
+            for (cell, cave) in grid.entries_mut() {
+                *cave &= is_solid(cell);
+            }
+            grid
+        });
+    }
+
+    progress.task(|| remove_small_caves(&mut cave_map, settings.min_area));
+
+    cave_map
+}
+
+fn remove_small_caves(cave_map: &mut Grid<bool>, min_area: usize) {
+    let _scope = info_span!("remove_small_caves").entered();
+
+    let mut visited = Grid::new(cave_map.size(), false);
+    let mut region = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for cell in cave_map.cells() {
+        if visited[cell] || !cave_map[cell] {
+            continue;
+        }
+
+        region.clear();
+        visited[cell] = true;
+        queue.push_back(cell);
+
+        while let Some(cell) = queue.pop_front() {
+            region.push(cell);
+
+            for (_, neighbor) in cave_map.neighborhood_4(cell) {
+                if !visited[neighbor] && cave_map[neighbor] {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if region.len() < min_area {
+            for &cell in &region {
+                cave_map[cell] = false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+    use rg_core::progress::new_progress_tracker;
+    use rg_worldgen_api::WorldgenStage;
+
+    use super::*;
+
+    const SETTINGS: CavesSettings = CavesSettings {
+        min_height: 20.0,
+        fill_probability: 0.45,
+        steps: 5,
+        min_area: 16,
+    };
+
+    fn caves(seed: u64) -> Grid<bool> {
+        let (_, mut progress) = new_progress_tracker(None::<PathBuf>, None);
+        let height_map = Grid::new(UVec2::splat(64), 30.0);
+
+        generate_cave_map(
+            &mut Pcg32::seed_from_u64(seed),
+            &mut progress.stage(WorldgenStage::Caves),
+            &SETTINGS,
+            &height_map,
+        )
+    }
+
+    #[test]
+    fn deterministic_for_seed() {
+        let a = caves(3);
+        assert_eq!(a.data(), caves(3).data());
+        assert_ne!(a.data(), caves(4).data());
+    }
+
+    #[test]
+    fn small_caves_are_removed() {
+        let cave_map = caves(3);
+        let (labels, num_labels) = cave_map.connected_components_labeled();
+        assert!(num_labels > 0);
+
+        let mut areas = vec![0; num_labels + 1];
+        for &label in labels.values() {
+            areas[label as usize] += 1;
+        }
+
+        assert!(areas[1..].iter().all(|&area| area >= SETTINGS.min_area));
+    }
+}
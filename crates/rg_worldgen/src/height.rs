@@ -1,6 +1,5 @@
 use bevy::prelude::*;
-use rg_core::grid::Grid;
-use rg_core::noise::Noise;
+use rg_core::grid::{EdtSettings, Grid};
 use rg_core::progress::ProgressStage;
 use rg_worldgen_api::{HeightSettings, NoiseMaps};
 
@@ -13,6 +12,7 @@ pub fn generate_height_map(
     let _scope = info_span!("generate_height_map").entered();
 
     let mut height_map = progress.task(|| shape(settings, noise_maps, island));
+    progress.task(|| deepen_ocean(settings, &mut height_map));
 
     progress.task(|| height_map.blur(2));
     progress.task(|| height_map.blur(2));
@@ -20,10 +20,48 @@ pub fn generate_height_map(
     height_map
 }
 
+fn deepen_ocean(settings: &HeightSettings, height_map: &mut Grid<f32>) {
+    let _scope = info_span!("deepen_ocean").entered();
+
+    let coast_dist = height_map.to_bool(0.0).compute_edt(EdtSettings {
+        invert: true,
+        normalize: false,
+        padding: 0,
+    });
+
+    height_map.par_map_inplace(|cell, height| {
+        if *height < 0.0 {
+            let t = (coast_dist[cell] / settings.ocean_floor_distance.max(f32::EPSILON)).min(1.0);
+            *height -= settings.ocean_floor_depth * t * (2.0 - t);
+        }
+    });
+}
+
+#[derive(Debug, Clone)]
+pub struct SeaLevelPreview {
+    pub land_map: Grid<bool>,
+    pub land_fraction: f32,
+}
+
+/// Recomputes the land/ocean split for a candidate sea level without rerunning
+/// the rest of the pipeline.
+pub fn preview_sea_level(height_map: &Grid<f32>, sea_level: f32) -> SeaLevelPreview {
+    let _scope = info_span!("preview_sea_level").entered();
+
+    let land_map = height_map.to_bool(sea_level);
+    let land_cells = land_map.values().filter(|&&v| v).count();
+    let total_cells = land_map.data().len().max(1);
+
+    SeaLevelPreview {
+        land_map,
+        land_fraction: land_cells as f32 / total_cells as f32,
+    }
+}
+
 fn shape(settings: &HeightSettings, noise_maps: &NoiseMaps, island: &Grid<f32>) -> Grid<f32> {
     let _scope = info_span!("shape").entered();
 
-    let max_dist = island.max_value();
+    let max_dist = island.max_value().unwrap_or(0.0);
 
     Grid::par_from_fn(island.size(), |cell| {
         let dist = island[cell];
@@ -46,8 +84,10 @@ fn shape(settings: &HeightSettings, noise_maps: &NoiseMaps, island: &Grid<f32>)
             a2 * ((k * s2 * x) / (a2 * (1.0 - x.powi(2)))).tanh()
         };
 
-        let warp = Vec2::from(noise_maps.height_warp.get(cell.as_vec2())) * 2.0 - 1.0;
-        let warped_dist = island.sample(cell.as_vec2() + warp * settings.warp_dist);
+        let warped_pos = noise_maps
+            .height_warp
+            .get_warped(cell.as_vec2(), settings.warp_strength);
+        let warped_dist = island.sample(warped_pos);
         let alpha = (dist / settings.beach_size).min(1.0).max(0.0);
         let dist = dist * (1.0 - alpha) + warped_dist * alpha;
 
@@ -57,3 +97,121 @@ fn shape(settings: &HeightSettings, noise_maps: &NoiseMaps, island: &Grid<f32>)
         height
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+    use crate::tests::tiny_settings;
+
+    const SETTINGS: HeightSettings = HeightSettings {
+        beach_size: 40.0,
+        land_height: 5.0,
+        peak_height: 80.0,
+        ocean_depth: 80.0,
+        ocean_floor_depth: 120.0,
+        ocean_floor_distance: 20.0,
+        warp_strength: 200.0,
+        mountain_power: 2.0,
+    };
+
+    /// Land in the first 4 columns, shallow ocean everywhere else.
+    fn coast() -> Grid<f32> {
+        Grid::from_fn(
+            UVec2::new(32, 4),
+            |cell| if cell.x < 4 { 1.0 } else { -1.0 },
+        )
+    }
+
+    #[test]
+    fn deepen_ocean_increases_with_coast_distance() {
+        let mut height_map = coast();
+        deepen_ocean(&SETTINGS, &mut height_map);
+
+        let row = (0..32)
+            .map(|x| height_map[IVec2::new(x, 2)])
+            .collect::<Vec<_>>();
+
+        assert!(row[..4].iter().all(|&h| h == 1.0));
+        assert!(row[4..].windows(2).all(|w| w[1] <= w[0]));
+        assert!(row[4] > row[31]);
+        assert_eq!(row[31], -1.0 - SETTINGS.ocean_floor_depth);
+    }
+
+    #[test]
+    fn deepen_ocean_with_zero_floor_distance() {
+        let mut height_map = coast();
+        let settings = HeightSettings {
+            ocean_floor_distance: 0.0,
+            ..SETTINGS
+        };
+        deepen_ocean(&settings, &mut height_map);
+
+        assert!(height_map.values().all(|h| h.is_finite()));
+    }
+
+    /// A round island with a radius of 24 cells.
+    fn round_island() -> Grid<f32> {
+        Grid::from_fn(UVec2::new(64, 64), |cell| {
+            24.0 - (cell.as_vec2() - Vec2::splat(32.0)).length()
+        })
+    }
+
+    fn noise_maps(seed: u64) -> NoiseMaps {
+        NoiseMaps::new(&mut Pcg32::seed_from_u64(seed), &tiny_settings().noise)
+    }
+
+    #[test]
+    fn zero_warp_strength_ignores_warp_noise() {
+        let island = round_island();
+        let settings = HeightSettings {
+            warp_strength: 0.0,
+            ..SETTINGS
+        };
+
+        let a = shape(&settings, &noise_maps(1), &island);
+        let b = shape(&settings, &noise_maps(2), &island);
+
+        for (cell, &height) in a.entries() {
+            assert!((height - b[cell]).abs() < 1e-4, "{cell}");
+        }
+    }
+
+    #[test]
+    fn warp_strength_displaces_features() {
+        let island = round_island();
+        let noise_maps = noise_maps(1);
+        let unwarped = HeightSettings {
+            warp_strength: 0.0,
+            ..SETTINGS
+        };
+        let warped = HeightSettings {
+            warp_strength: 8.0,
+            ..SETTINGS
+        };
+
+        let a = shape(&unwarped, &noise_maps, &island);
+        let b = shape(&warped, &noise_maps, &island);
+
+        assert!(a
+            .entries()
+            .any(|(cell, &height)| (height - b[cell]).abs() > 1e-2));
+    }
+
+    #[test]
+    fn higher_sea_level_has_less_land() {
+        let height_map = round_island();
+
+        let low = preview_sea_level(&height_map, 0.0);
+        let high = preview_sea_level(&height_map, 10.0);
+
+        assert!(high.land_fraction < low.land_fraction);
+        assert!(high.land_fraction > 0.0);
+        assert!(high
+            .land_map
+            .entries()
+            .all(|(cell, &land)| !land || low.land_map[cell]));
+    }
+}
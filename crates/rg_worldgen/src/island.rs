@@ -1,8 +1,11 @@
+use std::cmp::Reverse;
+use std::f32::consts::TAU;
+
 use bevy::prelude::*;
 use rand::Rng;
-use rg_core::grid::{EdtSettings, Grid};
+use rg_core::grid::{EdtSettings, Grid, Neighborhood};
 use rg_core::progress::ProgressStage;
-use rg_worldgen_api::{IslandSettings, NoiseMaps};
+use rg_worldgen_api::{IslandMode, IslandSettings, NoiseMaps};
 
 pub fn generate_island_map<R: Rng>(
     rng: &mut R,
@@ -29,6 +32,10 @@ pub fn generate_island_map<R: Rng>(
         progress.task(|| remove_holes(&mut grid));
         progress.task(|| remove_small_islands(settings, &mut grid));
 
+        if settings.keep_largest_landmass && settings.mode == IslandMode::Single {
+            progress.task(|| keep_largest_landmass(&mut grid));
+        }
+
         if !check_total_area(&grid, settings) {
             continue;
         }
@@ -43,13 +50,40 @@ fn voronoi_reshape<R: Rng>(rng: &mut R, grid: &mut Grid<f32>, settings: &IslandS
     let size = grid.size().as_vec2();
     let margin = f32::min(size.x, size.y) * settings.reshape_margin;
 
+    let mut random_point = || {
+        Vec2::new(
+            rng.gen_range(margin..=(size.x - margin)),
+            rng.gen_range(margin..=(size.y - margin)),
+        )
+    };
+
     let mut points = [Vec2::ZERO; 32];
-    for point in points.iter_mut().skip(1) {
-        point.x = rng.gen_range(margin..=(size.x - margin));
-        point.y = rng.gen_range(margin..=(size.y - margin));
-    }
 
-    points[0] = size * 0.5;
+    match settings.mode {
+        IslandMode::Single => {
+            for point in points.iter_mut().skip(1) {
+                *point = random_point();
+            }
+
+            points[0] = size * 0.5;
+        }
+        IslandMode::Archipelago { islands } => {
+            let islands = (islands as usize).clamp(1, points.len());
+            let centers = (0..islands).map(|_| random_point()).collect::<Vec<_>>();
+            let spread = size.x.min(size.y) * settings.reshape_radius;
+
+            for (i, point) in points.iter_mut().enumerate() {
+                *point = match centers.get(i) {
+                    Some(&center) => center,
+                    None => {
+                        let center = centers[rng.gen_range(0..islands)];
+                        let dir = Vec2::from_angle(rng.gen_range(0.0..TAU));
+                        center + dir * rng.gen_range(0.0..spread)
+                    }
+                };
+            }
+        }
+    }
 
     for (cell, value) in grid.entries_mut() {
         let pos = cell.as_vec2();
@@ -92,6 +126,29 @@ fn remove_small_islands(settings: &IslandSettings, grid: &mut Grid<bool>) {
     }
 }
 
+fn keep_largest_landmass(grid: &mut Grid<bool>) {
+    let _scope = info_span!("keep_largest_landmass").entered();
+
+    let (labels, count) = grid.label_components(Neighborhood::Four);
+    if count <= 1 {
+        return;
+    }
+
+    let mut areas = vec![0usize; count + 1];
+    for &label in labels.values() {
+        areas[label as usize] += 1;
+    }
+
+    // ties go to the lowest label, keeping the result deterministic
+    let largest = (1..=count)
+        .max_by_key(|&label| (areas[label], Reverse(label)))
+        .unwrap_or(1);
+
+    for (cell, value) in grid.entries_mut() {
+        *value = labels[cell] as usize == largest;
+    }
+}
+
 fn floodfill(
     src: &Grid<bool>,
     src_value: bool,
@@ -222,3 +279,39 @@ fn generate_sdf(island: &Grid<bool>) -> Grid<f32> {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+    use rg_core::progress::new_progress_tracker;
+    use rg_worldgen_api::WorldgenStage;
+
+    use super::*;
+    use crate::tests::tiny_settings;
+
+    #[test]
+    fn archipelago_has_several_islands() {
+        let mut settings = tiny_settings();
+        settings.island.size = UVec2::new(256, 512);
+        settings.island.mode = IslandMode::Archipelago { islands: 5 };
+        settings.island.reshape_radius = 0.08;
+        settings.island.reshape_alpha = 0.8;
+
+        let rng = &mut Pcg32::seed_from_u64(1);
+        let noise_maps = NoiseMaps::new(rng, &settings.noise);
+        let (_, mut progress) = new_progress_tracker(None::<PathBuf>, None);
+        let island_map = generate_island_map(
+            rng,
+            &mut progress.stage(WorldgenStage::Island),
+            &settings.island,
+            &noise_maps,
+        );
+
+        let land_map = island_map.map(|_, &dist| dist > 0.0);
+        let (_, num_islands) = land_map.connected_components_labeled();
+        assert!(num_islands > 1, "{num_islands}");
+    }
+}
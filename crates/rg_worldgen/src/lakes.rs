@@ -0,0 +1,164 @@
+use std::collections::{BinaryHeap, VecDeque};
+
+use bevy::prelude::*;
+use rg_core::grid::Grid;
+use rg_core::progress::ProgressStage;
+use rg_worldgen_api::LakesSettings;
+
+pub fn generate_lake_map(
+    progress: &mut ProgressStage,
+    settings: &LakesSettings,
+    height_map: &Grid<f32>,
+) -> Grid<f32> {
+    let _scope = info_span!("generate_lake_map").entered();
+
+    let filled_map = progress.task(|| fill_depressions(height_map));
+    progress.task(|| extract_lakes(settings, height_map, &filled_map))
+}
+
+struct QueueItem {
+    level: f32,
+    cell: IVec2,
+}
+
+impl PartialEq for QueueItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level
+    }
+}
+
+impl Eq for QueueItem {}
+
+impl PartialOrd for QueueItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        f32::total_cmp(&other.level, &self.level)
+    }
+}
+
+fn fill_depressions(height_map: &Grid<f32>) -> Grid<f32> {
+    let _scope = info_span!("fill_depressions").entered();
+
+    let size = height_map.size().as_ivec2();
+    let mut filled_map = Grid::new(height_map.size(), f32::NAN);
+    let mut queue = BinaryHeap::new();
+
+    for cell in height_map.cells() {
+        if cell.x == 0 || cell.y == 0 || cell.x == size.x - 1 || cell.y == size.y - 1 {
+            let level = height_map[cell].max(0.0);
+            filled_map[cell] = level;
+            queue.push(QueueItem { level, cell });
+        }
+    }
+
+    while let Some(item) = queue.pop() {
+        for (_, neighbor) in height_map.neighborhood_4(item.cell) {
+            if !filled_map[neighbor].is_nan() {
+                continue;
+            }
+
+            let level = height_map[neighbor].max(item.level);
+            filled_map[neighbor] = level;
+            queue.push(QueueItem {
+                level,
+                cell: neighbor,
+            });
+        }
+    }
+
+    filled_map
+}
+
+fn extract_lakes(
+    settings: &LakesSettings,
+    height_map: &Grid<f32>,
+    filled_map: &Grid<f32>,
+) -> Grid<f32> {
+    let _scope = info_span!("extract_lakes").entered();
+
+    let is_lake = |cell: IVec2| {
+        let level = filled_map[cell];
+        level > 0.0 && level - height_map[cell] > settings.min_depth
+    };
+
+    let mut lake_map = Grid::new(height_map.size(), f32::NAN);
+    let mut visited = Grid::new(height_map.size(), false);
+    let mut basin = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for cell in height_map.cells() {
+        if visited[cell] || !is_lake(cell) {
+            continue;
+        }
+
+        basin.clear();
+        visited[cell] = true;
+        queue.push_back(cell);
+
+        let mut below_sea_level = false;
+
+        while let Some(cell) = queue.pop_front() {
+            basin.push(cell);
+            below_sea_level |= height_map[cell] < 0.0;
+
+            for (_, neighbor) in height_map.neighborhood_4(cell) {
+                if !visited[neighbor] && is_lake(neighbor) {
+                    visited[neighbor] = true;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        if !below_sea_level {
+            continue;
+        }
+
+        for &cell in &basin {
+            lake_map[cell] = filled_map[cell];
+        }
+    }
+
+    lake_map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basin_fills_to_spill_level() {
+        // sea level border, a rim at 0.5 with a notch at 0.3 and a basin floor
+        // below sea level
+        let height_map = Grid::from_fn(UVec2::splat(7), |cell| {
+            let border = cell.x == 0 || cell.y == 0 || cell.x == 6 || cell.y == 6;
+            let rim = cell.x == 1 || cell.y == 1 || cell.x == 5 || cell.y == 5;
+            if border {
+                0.0
+            } else if cell == IVec2::new(3, 1) {
+                0.3
+            } else if rim {
+                0.5
+            } else {
+                -1.0
+            }
+        });
+
+        let settings = LakesSettings { min_depth: 0.1 };
+        let filled_map = fill_depressions(&height_map);
+        let lake_map = extract_lakes(&settings, &height_map, &filled_map);
+
+        for cell in height_map.cells() {
+            let inside = (2..=4).contains(&cell.x) && (2..=4).contains(&cell.y);
+            if inside {
+                assert_eq!(lake_map[cell], 0.3, "{cell}");
+            } else {
+                assert!(lake_map[cell].is_nan(), "{cell}");
+            }
+        }
+    }
+}
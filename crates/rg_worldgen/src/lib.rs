@@ -1,30 +1,39 @@
 mod biomes;
+mod caves;
 mod height;
 mod island;
+mod lakes;
 mod progress;
 mod rivers;
 mod shores;
+mod temperature;
 mod topography;
 
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use bevy::prelude::*;
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
-use rand::SeedableRng;
 use rand_pcg::Pcg32;
-use rg_core::progress::new_progress_tracker;
+use rg_core::progress::{new_progress_tracker, ProgressWriter};
 use rg_worldgen_api::{
     NoiseMaps, SharedWorldMaps, WorldMaps, WorldSeed, WorldgenApiPlugin, WorldgenProgress,
-    WorldgenSettings, WorldgenStage, WorldgenState,
+    WorldgenSettings, WorldgenStage, WorldgenState, SEA_LEVEL,
 };
 
+pub use crate::biomes::classify_biome;
 use crate::biomes::generate_biome_map;
+use crate::caves::generate_cave_map;
 use crate::height::generate_height_map;
+pub use crate::height::{preview_sea_level, SeaLevelPreview};
 use crate::island::generate_island_map;
+use crate::lakes::generate_lake_map;
+pub use crate::progress::bake_progress;
 use crate::progress::WorldgenProgressUiPlugin;
 use crate::rivers::generate_river_map;
 use crate::shores::generate_shore_map;
+use crate::temperature::generate_temperature_map;
 use crate::topography::generate_topographic_map;
 
 pub struct WorldgenPlugin;
@@ -53,7 +62,7 @@ struct WorldgenTask(pub Task<WorldMaps>);
 fn schedule_task(seed: Res<WorldSeed>, settings: Res<WorldgenSettings>, mut commands: Commands) {
     let pool = AsyncComputeTaskPool::get();
     let seed = seed.0;
-    let settings = *settings;
+    let settings = settings.clone();
 
     let tmp_dir = std::env::temp_dir();
     let (progress_reader, mut progress) = new_progress_tracker(
@@ -68,8 +77,9 @@ fn schedule_task(seed: Res<WorldSeed>, settings: Res<WorldgenSettings>, mut comm
 
         let tmp_dir = &tmp_dir;
         let path = tmp_dir.join("world.bin");
+        let dump = DebugDump::new(settings.debug_dump_dir.clone());
 
-        if path.exists() {
+        if path.exists() && !dump.is_enabled() {
             match WorldMaps::load(&path) {
                 Ok(world_maps) => return world_maps,
                 Err(e) => {
@@ -78,82 +88,11 @@ fn schedule_task(seed: Res<WorldSeed>, settings: Res<WorldgenSettings>, mut comm
             }
         }
 
-        let mut rng = Pcg32::seed_from_u64(seed);
-        let noise_maps = NoiseMaps::new(&mut rng, &settings.noise);
-
-        let island_map = generate_island_map(
-            &mut rng,
-            &mut progress.stage(WorldgenStage::Island),
-            &settings.island,
-            &noise_maps,
-        );
-
-        let mut height_map = generate_height_map(
-            &mut progress.stage(WorldgenStage::Height),
-            &settings.height,
-            &noise_maps,
-            &island_map,
-        );
-
-        let river_map = generate_river_map(
-            &mut rng,
-            &mut progress.stage(WorldgenStage::Rivers),
-            &settings.rivers,
-            &island_map,
-            &mut height_map,
-        );
-
-        let shore_map = generate_shore_map(
-            &mut progress.stage(WorldgenStage::Shores),
-            &island_map,
-            &river_map,
-        );
-
-        let biome_map = generate_biome_map(
-            &mut progress.stage(WorldgenStage::Biomes),
-            &noise_maps,
-            &height_map,
-        );
-
-        let topographic_map = generate_topographic_map(
-            &mut progress.stage(WorldgenStage::Topography),
-            &settings.topography,
-            &height_map,
-        );
-
-        let maps = [
-            ("island_map", &island_map),
-            ("height_map", &height_map),
-            ("river_map", &river_map),
-            ("shore_map", &shore_map),
-        ];
-
-        let mut saving_stage = progress.stage(WorldgenStage::Saving);
-
-        saving_stage.multi_task(4, |task| {
-            rayon::scope(|s| {
-                for (name, grid) in maps {
-                    let task = &task;
-                    s.spawn(move |_| {
-                        grid.debug_save(tmp_dir.join(format!("{name}.png")));
-                        task.subtask_completed();
-                    });
-                }
-            });
-        });
+        let world_maps = generate_world(seed, &settings, &mut progress, &dump, tmp_dir, None);
 
-        saving_stage.task(|| topographic_map.debug_save(tmp_dir.join("topographic_map.png")));
-
-        let world_maps = WorldMaps {
-            seed,
-            noise_maps,
-            height_map,
-            river_map,
-            shore_map,
-            biome_map,
-        };
-
-        saving_stage.task(|| world_maps.save(path).unwrap());
+        progress
+            .stage(WorldgenStage::Saving)
+            .task(|| world_maps.save(path).unwrap());
         progress.finish();
 
         world_maps
@@ -162,6 +101,242 @@ fn schedule_task(seed: Res<WorldSeed>, settings: Res<WorldgenSettings>, mut comm
     commands.insert_resource(WorldgenTask(task));
 }
 
+/// Receives the fraction of completed stages, from 0 to 1.
+pub type ProgressCallback = Box<dyn Fn(f32) + Send>;
+
+/// Generates the world on the current thread, without the Bevy app.
+pub fn generate_world_blocking(
+    seed: u64,
+    settings: &WorldgenSettings,
+    progress_callback: Option<ProgressCallback>,
+) -> WorldMaps {
+    let _scope = info_span!("worldgen").entered();
+
+    let (_, mut progress) = new_progress_tracker(None::<PathBuf>, None);
+    let dump = DebugDump::new(settings.debug_dump_dir.clone());
+    let tmp_dir = std::env::temp_dir();
+    generate_world(
+        seed,
+        settings,
+        &mut progress,
+        &dump,
+        &tmp_dir,
+        progress_callback,
+    )
+}
+
+fn generate_world(
+    seed: u64,
+    settings: &WorldgenSettings,
+    progress: &mut ProgressWriter<WorldgenStage>,
+    dump: &DebugDump,
+    tmp_dir: &Path,
+    progress_callback: Option<ProgressCallback>,
+) -> WorldMaps {
+    let mut stages = StageCounter::new(progress_callback);
+
+    let noise_maps = NoiseMaps::new(&mut stage_rng(seed, WorldgenStage::Init), &settings.noise);
+    stages.completed();
+
+    let island_map = generate_island_map(
+        &mut stage_rng(seed, WorldgenStage::Island),
+        &mut progress.stage(WorldgenStage::Island),
+        &settings.island,
+        &noise_maps,
+    );
+
+    dump.save("island_map", |path| island_map.save_png(path));
+    stages.completed();
+
+    let mut height_map = generate_height_map(
+        &mut progress.stage(WorldgenStage::Height),
+        &settings.height,
+        &noise_maps,
+        &island_map,
+    );
+
+    dump.save("height_map_pre_erosion", |path| height_map.save_png(path));
+    stages.completed();
+    let pre_erosion_height_map = dump.is_enabled().then(|| height_map.clone());
+
+    let (river_map, flow_map) = generate_river_map(
+        seed,
+        &mut progress.stage(WorldgenStage::Rivers),
+        &settings.rivers,
+        &island_map,
+        &mut height_map,
+    );
+
+    dump.save("height_map", |path| height_map.save_png(path));
+    dump.save("river_map", |path| river_map.save_png(path));
+
+    if let Some(pre_erosion_height_map) = &pre_erosion_height_map {
+        let erosion_map = pre_erosion_height_map.map(|cell, &h| h - height_map[cell]);
+        dump.save("erosion_map", |path| erosion_map.save_png(path));
+    }
+
+    stages.completed();
+
+    let lake_map = generate_lake_map(
+        &mut progress.stage(WorldgenStage::Lakes),
+        &settings.lakes,
+        &height_map,
+    );
+
+    dump.save("lake_map", |path| lake_map.save_png(path));
+    stages.completed();
+
+    let cave_map = generate_cave_map(
+        &mut stage_rng(seed, WorldgenStage::Caves),
+        &mut progress.stage(WorldgenStage::Caves),
+        &settings.caves,
+        &height_map,
+    );
+
+    dump.save("cave_map", |path| cave_map.save_png(path));
+    stages.completed();
+
+    let shore_map = generate_shore_map(
+        &mut progress.stage(WorldgenStage::Shores),
+        &island_map,
+        &river_map,
+    );
+
+    dump.save("shore_map", |path| shore_map.save_png(path));
+    stages.completed();
+
+    let temperature_map = generate_temperature_map(
+        &mut progress.stage(WorldgenStage::Temperature),
+        &settings.temperature,
+        &noise_maps,
+        &height_map,
+    );
+
+    dump.save("temperature_map", |path| temperature_map.save_png(path));
+    stages.completed();
+
+    let biome_map = generate_biome_map(
+        &mut progress.stage(WorldgenStage::Biomes),
+        &settings.temperature,
+        &noise_maps,
+        &height_map,
+        &temperature_map,
+        SEA_LEVEL,
+    );
+
+    stages.completed();
+
+    let topographic_map = generate_topographic_map(
+        &mut progress.stage(WorldgenStage::Topography),
+        &settings.topography,
+        &height_map,
+    );
+
+    dump.save("topographic_map", |path| topographic_map.save_png(path));
+    stages.completed();
+
+    let maps = [
+        ("island_map", &island_map),
+        ("height_map", &height_map),
+        ("river_map", &river_map),
+        ("lake_map", &lake_map),
+        ("shore_map", &shore_map),
+        ("temperature_map", &temperature_map),
+    ];
+
+    let mut saving_stage = progress.stage(WorldgenStage::Saving);
+
+    saving_stage.multi_task(maps.len(), |task| {
+        rayon::scope(|s| {
+            for (name, grid) in maps {
+                let task = &task;
+                s.spawn(move |_| {
+                    grid.debug_save(tmp_dir.join(format!("{name}.png")));
+                    task.subtask_completed();
+                });
+            }
+        });
+    });
+
+    saving_stage.task(|| topographic_map.debug_save(tmp_dir.join("topographic_map.png")));
+    saving_stage.task(|| cave_map.debug_save(tmp_dir.join("cave_map.png")));
+    stages.completed();
+
+    WorldMaps {
+        seed,
+        noise_maps,
+        height_map,
+        river_map,
+        flow_map,
+        lake_map,
+        cave_map,
+        shore_map,
+        temperature_map,
+        biome_map,
+    }
+}
+
+const NUM_STAGES: u32 = WorldgenStage::Saving as u32 + 1;
+
+/// Reports a running count of completed stages, so the reported progress
+/// never decreases regardless of the order stages run in.
+struct StageCounter {
+    callback: Option<ProgressCallback>,
+    completed: u32,
+}
+
+impl StageCounter {
+    fn new(callback: Option<ProgressCallback>) -> StageCounter {
+        StageCounter {
+            callback,
+            completed: 0,
+        }
+    }
+
+    fn completed(&mut self) {
+        self.completed = (self.completed + 1).min(NUM_STAGES);
+
+        if let Some(callback) = &self.callback {
+            callback(self.completed as f32 / NUM_STAGES as f32);
+        }
+    }
+}
+
+struct DebugDump {
+    dir: Option<PathBuf>,
+}
+
+impl DebugDump {
+    fn new(dir: Option<PathBuf>) -> DebugDump {
+        if let Some(dir) = &dir {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                warn!("cannot create debug dump directory: {e:?}");
+                return DebugDump { dir: None };
+            }
+        }
+
+        DebugDump { dir }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    fn save(&self, name: &str, save: impl FnOnce(&Path) -> anyhow::Result<()>) {
+        let Some(dir) = &self.dir else {
+            return;
+        };
+
+        if let Err(e) = save(&dir.join(format!("{name}.png"))) {
+            warn!("cannot dump {name}: {e:?}");
+        }
+    }
+}
+
+fn stage_rng(seed: u64, stage: WorldgenStage) -> Pcg32 {
+    Pcg32::new(seed, u32::from(stage).into())
+}
+
 fn update_task(
     mut task: ResMut<WorldgenTask>,
     mut next_state: ResMut<NextState<WorldgenState>>,
@@ -174,3 +349,90 @@ fn update_task(
         next_state.set(WorldgenState::Done);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use bevy::utils::HashMap;
+
+    use super::*;
+
+    /// Default settings shrunk to a 64x128 map that generates in milliseconds.
+    pub(crate) fn tiny_settings() -> WorldgenSettings {
+        let settings = include_str!("../../../assets/default.worldgen.ron");
+        let mut settings = ron::de::from_str::<WorldgenSettings>(settings).unwrap();
+        settings.island.size = UVec2::new(64, 128);
+        settings.island.min_total_area = 0.0;
+        settings.island.max_total_area = 1.0;
+        settings
+    }
+
+    #[test]
+    fn stage_rng_is_independent_of_stage_order() {
+        use rand::Rng;
+        use WorldgenStage::{Caves, Height, Island};
+
+        let run = |order: &[WorldgenStage]| {
+            let mut outputs = HashMap::new();
+            for &stage in order {
+                let rng = &mut stage_rng(7, stage);
+                let values = (0..16).map(|_| rng.gen::<u32>()).collect::<Vec<_>>();
+                outputs.insert(u32::from(stage), values);
+            }
+            outputs
+        };
+
+        let a = run(&[Island, Height, Caves]);
+        let b = run(&[Height, Island, Caves]);
+
+        let caves = u32::from(Caves);
+        assert_eq!(a[&caves], b[&caves]);
+        assert_ne!(a[&u32::from(Island)], a[&caves]);
+        assert_ne!(a[&u32::from(Height)], a[&caves]);
+    }
+
+    #[test]
+    fn debug_dump_writes_every_stage() {
+        let dir = std::env::temp_dir().join(format!("rg_worldgen_dump_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut settings = tiny_settings();
+        settings.debug_dump_dir = Some(dir.clone());
+        generate_world_blocking(0, &settings, None);
+
+        for name in [
+            "island_map",
+            "height_map_pre_erosion",
+            "height_map",
+            "erosion_map",
+            "river_map",
+            "lake_map",
+            "cave_map",
+            "shore_map",
+            "temperature_map",
+            "topographic_map",
+        ] {
+            assert!(
+                dir.join(format!("{name}.png")).is_file(),
+                "{name} is missing"
+            );
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stage_progress_is_monotonic() {
+        let values = Arc::new(Mutex::new(Vec::new()));
+        let sink = values.clone();
+
+        let callback: ProgressCallback = Box::new(move |v| sink.lock().unwrap().push(v));
+        generate_world_blocking(0, &tiny_settings(), Some(callback));
+
+        let values = values.lock().unwrap();
+        assert_eq!(values.len(), NUM_STAGES as usize);
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(values.last().copied(), Some(1.0));
+    }
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::*;
-use rg_worldgen_api::WorldgenProgress;
+use rg_core::progress::new_progress_tracker;
+use rg_worldgen_api::{WorldgenProgress, WorldgenSettings};
 
-use crate::WorldgenState;
+use crate::{generate_world, DebugDump, WorldgenState};
 
 rg_core::progress_stages! {
     pub enum WorldgenStage {
@@ -9,13 +10,30 @@ rg_core::progress_stages! {
         Island => "Generating the island...",
         Height => "Raising mountains...",
         Rivers => "Forming rivers...",
+        Lakes => "Filling lakes...",
+        Caves => "Carving caves...",
         Shores => "Generating shores...",
+        Temperature => "Measuring temperatures...",
         Biomes => "Generating biomes...",
         Topography => "Mapping the world...",
         Saving => "Saving the world...",
     }
 }
 
+/// Runs the whole generator with sampling enabled and returns the data for
+/// `progress.bin`.
+pub fn bake_progress(seed: u64, settings: &WorldgenSettings) -> Vec<u8> {
+    let _scope = info_span!("bake_progress").entered();
+
+    let tmp_dir = std::env::temp_dir();
+    let (_, mut progress) = new_progress_tracker(Some(tmp_dir.join("worldgen_progress.bin")), None);
+
+    let dump = DebugDump::new(None);
+    generate_world(seed, settings, &mut progress, &dump, &tmp_dir, None);
+
+    progress.bake().unwrap_or_default()
+}
+
 pub struct WorldgenProgressUiPlugin;
 
 impl Plugin for WorldgenProgressUiPlugin {
@@ -105,3 +123,34 @@ fn destroy_ui(root: Res<UiRoot>, mut commands: Commands) {
     commands.entity(root.0).despawn_recursive();
     commands.remove_resource::<UiRoot>();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ops::Range;
+
+    use super::*;
+    use crate::tests::tiny_settings;
+
+    type Samples = (HashMap<u32, Range<f32>>, HashMap<(u32, u32), Range<f32>>);
+
+    #[test]
+    fn baked_stages_cover_whole_range() {
+        let data = bake_progress(0, &tiny_settings());
+        let (stages, tasks) = rmp_serde::from_slice::<Samples>(&data).unwrap();
+
+        let mut stages = stages.into_iter().collect::<Vec<_>>();
+        stages.sort_by_key(|&(stage, _)| stage);
+        let ranges = stages.into_iter().map(|(_, r)| r).collect::<Vec<_>>();
+
+        assert_eq!(ranges.first().map(|r| r.start), Some(0.0));
+        assert!(ranges.windows(2).all(|w| w[0].end == w[1].start));
+
+        let total = ranges.iter().map(|r| r.end - r.start).sum::<f32>();
+        assert!((total - 1.0).abs() < 1e-4);
+        assert!((ranges.last().unwrap().end - 1.0).abs() < 1e-4);
+
+        assert!(!tasks.is_empty());
+        assert!(tasks.values().all(|r| 0.0 <= r.start && r.end <= 1.0));
+    }
+}
@@ -1,26 +1,29 @@
 use std::collections::BinaryHeap;
 
 use bevy::prelude::*;
-use rand::Rng;
 use raqote::{
     AntialiasMode, DrawOptions, DrawTarget, LineCap, LineJoin, Path, PathBuilder, SolidSource,
     Source, StrokeStyle,
 };
-use rg_core::grid::Grid;
+use rg_core::grid::{aa_line, Grid};
 use rg_core::progress::ProgressStage;
 use rg_core::PoissonDiscSampling;
 use rg_worldgen_api::RiversSettings;
 
-pub fn generate_river_map<R: Rng>(
-    rng: &mut R,
+/// Mixed into the world seed, so that the river points don't correlate with
+/// other samplings seeded from it.
+const POINTS_SEED: u64 = 0x6a09_e667_f3bc_c908;
+
+pub fn generate_river_map(
+    seed: u64,
     progress: &mut ProgressStage,
     settings: &RiversSettings,
     island_map: &Grid<f32>,
     height_map: &mut Grid<f32>,
-) -> Grid<f32> {
+) -> (Grid<f32>, Grid<Vec2>) {
     let _scope = info_span!("generate_river_map").entered();
 
-    let points = progress.task(|| generate_points(rng, height_map, settings));
+    let points = progress.task(|| generate_points(seed, height_map, settings));
 
     let mut queue = BinaryHeap::new();
     progress.task(|| initialize_queue(&mut queue, &points));
@@ -38,7 +41,12 @@ pub fn generate_river_map<R: Rng>(
 
     let strahler = progress.task(|| compute_strahler(&points, &upstream));
 
-    progress.task(|| draw_rivers(&points, island_map, &downstream, &upstream, &strahler))
+    let flow_map = progress.task(|| generate_flow_map(&points, island_map.size(), &downstream));
+
+    let river_map =
+        progress.task(|| draw_rivers(&points, island_map, &downstream, &upstream, &strahler));
+
+    (river_map, flow_map)
 }
 
 #[derive(Default)]
@@ -49,17 +57,14 @@ struct Points {
     neighbors: Vec<Vec<usize>>,
 }
 
-fn generate_points<R: Rng>(
-    rng: &mut R,
-    height_map: &Grid<f32>,
-    settings: &RiversSettings,
-) -> Points {
+fn generate_points(seed: u64, height_map: &Grid<f32>, settings: &RiversSettings) -> Points {
     let _scope = info_span!("generate_points").entered();
 
     let mut points = Points::default();
 
+    let size = height_map.size().as_vec2();
     points.positions =
-        PoissonDiscSampling::new(rng, height_map.size().as_vec2(), settings.point_radius, 8).points;
+        PoissonDiscSampling::new_seeded(seed ^ POINTS_SEED, size, settings.point_radius, 8).points;
     points.count = points.positions.len();
 
     let iter = points.positions.iter();
@@ -256,7 +261,7 @@ fn generate_erosion_map(
 ) -> Grid<f32> {
     let _scope = info_span!("generate_erosion_map").entered();
 
-    let max_height = height_map.max_value();
+    let max_height = height_map.max_value().unwrap_or(0.0);
 
     let mut erosion_map = Grid::new(height_map.size(), 0.0);
 
@@ -295,13 +300,50 @@ fn generate_erosion_map(
     erosion_map
 }
 
+fn generate_flow_map(points: &Points, size: UVec2, downstream: &[Option<usize>]) -> Grid<Vec2> {
+    let _scope = info_span!("generate_flow_map").entered();
+
+    let mut flow_x = Grid::new(size, 0.0);
+    let mut flow_y = Grid::new(size, 0.0);
+
+    for start_i in 0..points.count {
+        let Some(end_i) = downstream[start_i] else {
+            continue;
+        };
+
+        let start = points.positions[start_i];
+        let end = points.positions[end_i];
+        let dir = (end - start).normalize_or_zero();
+
+        aa_line(start, end, |cell, alpha| {
+            if !flow_x.contains_cell(cell) {
+                return;
+            }
+
+            flow_x[cell] += dir.x * alpha;
+            flow_y[cell] += dir.y * alpha;
+        });
+    }
+
+    flow_x.blur(3);
+    flow_x.blur(3);
+    flow_y.blur(3);
+    flow_y.blur(3);
+
+    Grid::par_from_fn(size, |cell| {
+        Vec2::new(flow_x[cell], flow_y[cell]).normalize_or_zero()
+    })
+}
+
 fn apply_erosion(erosion_map: &Grid<f32>, height_map: &mut Grid<f32>, settings: &RiversSettings) {
     let _scope = info_span!("apply_erosion").entered();
 
     for (cell, height_map) in height_map.entries_mut() {
-        let unscaled = 1.0 / (1.0 + erosion_map[cell].powf(1.1));
+        let erosion = erosion_map[cell] * settings.erosion_strength;
+        let unscaled = 1.0 / (1.0 + erosion.max(0.0).powf(settings.erosion_exponent));
         let fac = unscaled * settings.erosion + (1.0 - settings.erosion);
-        *height_map *= fac;
+        // scaling by a factor in 0..=1 never pushes land below sea level
+        *height_map *= fac.clamp(0.0, 1.0);
     }
 }
 
@@ -564,72 +606,58 @@ fn thomas_algorithm(
     path.finish()
 }
 
-fn aa_line(start: Vec2, end: Vec2, mut callback: impl FnMut(IVec2, f32)) {
-    let mut plot = |x, y, f| callback(IVec2::new(x, y), f);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: RiversSettings = RiversSettings {
+        point_radius: 2.0,
+        inertia: 0.3,
+        evaporation: 0.2,
+        erosion: 0.2,
+        erosion_exponent: 1.1,
+        erosion_strength: 1.0,
+    };
 
-    let mut x0 = start.x;
-    let mut y0 = start.y;
-    let mut x1 = end.x;
-    let mut y1 = end.y;
+    #[test]
+    fn same_seed_same_points() {
+        let height_map = Grid::new(UVec2::splat(64), 1.0);
 
-    let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let a = generate_points(42, &height_map, &SETTINGS);
+        let b = generate_points(42, &height_map, &SETTINGS);
+        assert_eq!(a.positions, b.positions);
 
-    if steep {
-        std::mem::swap(&mut x0, &mut y0);
-        std::mem::swap(&mut x1, &mut y1);
+        let c = generate_points(43, &height_map, &SETTINGS);
+        assert_ne!(a.positions, c.positions);
     }
-    if x0 > x1 {
-        std::mem::swap(&mut x0, &mut x1);
-        std::mem::swap(&mut y0, &mut y1);
-    }
-
-    let dx = x1 - x0;
-    let dy = y1 - y0;
 
-    let gradient = if dx == 0.0 { 1.0 } else { dy / dx };
+    #[test]
+    fn points_are_not_seeded_with_the_world_seed() {
+        let height_map = Grid::new(UVec2::splat(64), 1.0);
+        let points = generate_points(42, &height_map, &SETTINGS);
 
-    // handle first endpoint
-    let xend = x0.round();
-    let yend = y0 + gradient * (xend - x0);
-    let xgap = 1.0 - (x0 + 0.5).fract();
-    let xpxl1 = xend;
-    let ypxl1 = yend.trunc();
-    if steep {
-        plot(ypxl1 as i32, xpxl1 as i32, (1.0 - yend.fract()) * xgap);
-        plot(ypxl1 as i32 + 1, xpxl1 as i32, yend.fract() * xgap);
-    } else {
-        plot(xpxl1 as i32, ypxl1 as i32, (1.0 - (yend).fract()) * xgap);
-        plot(xpxl1 as i32, ypxl1 as i32 + 1, yend.fract() * xgap);
+        let size = height_map.size().as_vec2();
+        let raw = PoissonDiscSampling::new_seeded(42, size, SETTINGS.point_radius, 8).points;
+        assert_ne!(points.positions, raw);
     }
 
-    let mut intery = yend + gradient;
-
-    // handle second endpoint
-    let xend = x1.round();
-    let yend = y1 + gradient * (xend - x1);
-    let xgap = (x1 + 0.5).fract();
-    let xpxl2 = xend;
-    let ypxl2 = (yend).trunc();
-    if steep {
-        plot(ypxl2 as i32, xpxl2 as i32, (1.0 - yend.fract()) * xgap);
-        plot(ypxl2 as i32 + 1, xpxl2 as i32, yend.fract() * xgap);
-    } else {
-        plot(xpxl2 as i32, ypxl2 as i32, (1.0 - yend.fract()) * xgap);
-        plot(xpxl2 as i32, ypxl2 as i32, yend.fract() * xgap);
-    }
+    #[test]
+    fn stronger_erosion_lowers_land() {
+        let erosion_map = Grid::from_fn(UVec2::splat(16), |cell| cell.x as f32 * 0.5);
 
-    // main loop
-    if steep {
-        for x in (xpxl1 as i32 + 1)..=(xpxl2 as i32 - 1) {
-            plot(intery as i32, x, 1.0 - intery.fract());
-            plot(intery as i32 + 1, x, intery.fract());
-            intery += gradient;
-        }
-    } else {
-        for x in (xpxl1 as i32 + 1)..=(xpxl2 as i32 - 1) {
-            plot(x, intery as i32, 1.0 - intery.fract());
-            plot(x, intery as i32 + 1, intery.fract());
-            intery += gradient
-        }
+        let mean_height = |erosion_strength| {
+            let mut height_map = Grid::new(UVec2::splat(16), 10.0);
+            let settings = RiversSettings {
+                erosion_strength,
+                ..SETTINGS
+            };
+            apply_erosion(&erosion_map, &mut height_map, &settings);
+            height_map.values().sum::<f32>() / height_map.data().len() as f32
+        };
+
+        let heights = [0.0, 0.5, 1.0, 2.0, 4.0].map(mean_height);
+        assert_eq!(heights[0], 10.0);
+        assert!(heights.windows(2).all(|w| w[1] < w[0]), "{heights:?}");
+        assert!(heights.iter().all(|&h| h > 0.0));
     }
 }
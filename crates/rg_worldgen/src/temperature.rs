@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use rg_core::grid::Grid;
+use rg_core::noise::Noise;
+use rg_core::progress::ProgressStage;
+use rg_worldgen_api::{NoiseMaps, TemperatureSettings};
+
+pub fn generate_temperature_map(
+    progress: &mut ProgressStage,
+    settings: &TemperatureSettings,
+    noise_maps: &NoiseMaps,
+    height_map: &Grid<f32>,
+) -> Grid<f32> {
+    let _scope = info_span!("generate_temperature_map").entered();
+
+    let size = height_map.size();
+    progress.task(|| {
+        Grid::par_from_fn(size, |cell| {
+            let latitude = ((cell.y as f32 + 0.5) / (size.y as f32) * 2.0 - 1.0).abs();
+            let noise = noise_maps.temperature.get(cell.as_vec2())[0] * 2.0 - 1.0;
+            temperature(settings, latitude, height_map[cell]) + noise * settings.noise_strength
+        })
+    })
+}
+
+fn temperature(settings: &TemperatureSettings, latitude: f32, height: f32) -> f32 {
+    let base = settings.equator_temperature
+        + (settings.pole_temperature - settings.equator_temperature) * latitude;
+    base - settings.lapse_rate * height.max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: TemperatureSettings = TemperatureSettings {
+        equator_temperature: 28.0,
+        pole_temperature: 0.0,
+        lapse_rate: 0.25,
+        noise_strength: 3.0,
+        tundra_below: 2.0,
+        desert_above: 26.0,
+    };
+
+    #[test]
+    fn decreases_with_latitude() {
+        let temperatures = (0..=10)
+            .map(|i| temperature(&SETTINGS, i as f32 / 10.0, 0.0))
+            .collect::<Vec<_>>();
+
+        assert!(temperatures.windows(2).all(|w| w[0] > w[1]));
+        assert_eq!(temperatures[0], SETTINGS.equator_temperature);
+        assert_eq!(temperatures[10], SETTINGS.pole_temperature);
+    }
+
+    #[test]
+    fn decreases_with_elevation() {
+        let temperatures = (0..=10)
+            .map(|i| temperature(&SETTINGS, 0.3, i as f32 * 5.0))
+            .collect::<Vec<_>>();
+
+        assert!(temperatures.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn ocean_is_not_warmer_than_sea_level() {
+        let sea_level = temperature(&SETTINGS, 0.3, 0.0);
+        assert_eq!(temperature(&SETTINGS, 0.3, -20.0), sea_level);
+    }
+}
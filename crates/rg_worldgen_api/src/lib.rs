@@ -7,7 +7,7 @@ use std::path::Path;
 use std::sync::Arc;
 
 use bevy::prelude::*;
-use bytemuck::{CheckedBitPattern, NoUninit};
+use bytemuck::{cast_slice, CheckedBitPattern, NoUninit};
 use rand::Rng;
 use rg_core::grid::Grid;
 use rg_core::noise::FbmNoise;
@@ -19,6 +19,9 @@ pub use self::settings::*;
 
 pub const WORLD_SCALE: f32 = 2.0;
 
+/// Cells with a height below this are covered by the ocean.
+pub const SEA_LEVEL: f32 = 0.0;
+
 pub struct WorldgenApiPlugin;
 
 impl Plugin for WorldgenApiPlugin {
@@ -27,10 +30,20 @@ impl Plugin for WorldgenApiPlugin {
             .add_plugins(DeserializedResourcePlugin::<WorldgenSettings>::new(
                 "default.worldgen.ron",
             ))
-            .insert_resource(WorldSeed(0));
+            .insert_resource(WorldSeed(0))
+            .configure_sets(Update, WorldReady.run_if(world_ready));
     }
 }
 
+/// Systems that need the generated world. Only runs once `SharedWorldMaps`
+/// exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub struct WorldReady;
+
+pub fn world_ready(world_maps: Option<Res<SharedWorldMaps>>) -> bool {
+    world_maps.is_some()
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, States)]
 pub enum WorldgenState {
     #[default]
@@ -47,6 +60,8 @@ pub enum Biome {
     Ocean,
     Plains,
     Forest,
+    Tundra,
+    Desert,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,7 +70,11 @@ pub struct WorldMaps {
     pub noise_maps: NoiseMaps,
     pub height_map: Grid<f32>,
     pub river_map: Grid<f32>,
+    pub flow_map: Grid<Vec2>,
+    pub lake_map: Grid<f32>,
+    pub cave_map: Grid<bool>,
     pub shore_map: Grid<f32>,
+    pub temperature_map: Grid<f32>,
     pub biome_map: Grid<Biome>,
 }
 
@@ -78,6 +97,49 @@ impl WorldMaps {
         writer.flush()?;
         Ok(())
     }
+
+    /// Biome at a world-space position. Outside of the map is ocean.
+    pub fn biome_at(&self, pos: Vec2) -> Biome {
+        self.biome_map
+            .get((pos / WORLD_SCALE).as_ivec2())
+            .copied()
+            .unwrap_or(Biome::Ocean)
+    }
+
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = FNV_OFFSET_BASIS;
+        hash = fnv1a(hash, &self.seed.to_le_bytes());
+
+        let noise_maps = rmp_serde::to_vec(&self.noise_maps).unwrap_or_default();
+        hash = fnv1a(hash, &noise_maps);
+
+        hash = hash_grid(hash, &self.height_map);
+        hash = hash_grid(hash, &self.river_map);
+        hash = hash_grid(hash, &self.flow_map);
+        hash = hash_grid(hash, &self.lake_map);
+        hash = hash_grid(hash, &self.cave_map);
+        hash = hash_grid(hash, &self.shore_map);
+        hash = hash_grid(hash, &self.temperature_map);
+        hash = hash_grid(hash, &self.biome_map);
+        hash
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+fn hash_grid<T: NoUninit>(mut hash: u64, grid: &Grid<T>) -> u64 {
+    hash = fnv1a(hash, &grid.size().x.to_le_bytes());
+    hash = fnv1a(hash, &grid.size().y.to_le_bytes());
+    fnv1a(hash, cast_slice(grid.data()))
 }
 
 #[derive(Debug, Deref, Clone, Resource)]
@@ -89,6 +151,7 @@ pub struct NoiseMaps {
     pub height: FbmNoise,
     pub height_warp: FbmNoise<2>,
     pub biomes: FbmNoise,
+    pub temperature: FbmNoise,
     pub grass: FbmNoise,
 }
 
@@ -100,6 +163,56 @@ impl NoiseMaps {
             height_warp: FbmNoise::new(rng, &settings.height_warp),
             biomes: FbmNoise::new(rng, &settings.biomes),
             grass: FbmNoise::new(rng, &settings.grass),
+            // constructed last, so that the noises above keep their seeds
+            temperature: FbmNoise::new(rng, &settings.temperature),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use rand::SeedableRng;
+    use rand_pcg::Pcg32;
+
+    use super::*;
+
+    /// Small random maps, fully determined by `seed`.
+    fn world_maps(seed: u64) -> WorldMaps {
+        let settings = include_str!("../../../assets/default.worldgen.ron");
+        let settings = ron::de::from_str::<WorldgenSettings>(settings).unwrap();
+
+        let rng = &mut Pcg32::seed_from_u64(seed);
+        let size = UVec2::new(8, 8);
+        let noise_maps = NoiseMaps::new(rng, &settings.noise);
+        let height_map = Grid::from_fn(size, |_| rng.gen_range(-1.0f32..1.0));
+
+        WorldMaps {
+            seed,
+            noise_maps,
+            river_map: height_map.map(|_, &v| v.max(0.0)),
+            flow_map: height_map.map(|_, &v| Vec2::splat(v)),
+            lake_map: height_map.map(|_, &v| v.min(0.0)),
+            cave_map: height_map.map(|_, &v| v > 0.5),
+            shore_map: height_map.map(|_, &v| v.abs()),
+            temperature_map: height_map.map(|_, &v| 1.0 - v),
+            biome_map: height_map.map(|_, &v| if v > 0.0 { Biome::Plains } else { Biome::Ocean }),
+            height_map,
         }
     }
+
+    #[test]
+    fn content_hash_depends_on_seed() {
+        assert_eq!(world_maps(1).content_hash(), world_maps(1).content_hash());
+        assert_ne!(world_maps(1).content_hash(), world_maps(2).content_hash());
+    }
+
+    #[test]
+    fn world_ready_once_maps_are_shared() {
+        let mut world = World::new();
+        assert!(!world.run_system_once(world_ready));
+
+        world.insert_resource(SharedWorldMaps(Arc::new(world_maps(0))));
+        assert!(world.run_system_once(world_ready));
+    }
 }
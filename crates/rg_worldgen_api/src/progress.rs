@@ -7,7 +7,10 @@ rg_core::progress_stages! {
         Island => "Generating the island...",
         Height => "Raising mountains...",
         Rivers => "Forming rivers...",
+        Lakes => "Filling lakes...",
+        Caves => "Carving caves...",
         Shores => "Generating shores...",
+        Temperature => "Measuring temperatures...",
         Biomes => "Generating biomes...",
         Topography => "Mapping the world...",
         Saving => "Saving the world...",
@@ -1,17 +1,26 @@
+use std::path::PathBuf;
+
 use bevy::prelude::*;
 use bevy::reflect::{TypePath, TypeUuid};
 use rg_core::noise::FbmNoiseSettings;
 use rg_core::DeserializedResource;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Resource, Deserialize, TypePath, TypeUuid, Asset)]
+#[derive(Debug, Clone, Resource, Deserialize, TypePath, TypeUuid, Asset)]
 #[uuid = "9642a5f8-7606-4775-b5bc-6fda6d73bd84"]
 pub struct WorldgenSettings {
     pub noise: NoiseSettings,
     pub island: IslandSettings,
     pub height: HeightSettings,
     pub rivers: RiversSettings,
+    pub lakes: LakesSettings,
+    pub caves: CavesSettings,
+    pub temperature: TemperatureSettings,
     pub topography: TopographySettings,
+    /// When set, every intermediate map is saved there regardless of build
+    /// profile
+    #[serde(default)]
+    pub debug_dump_dir: Option<PathBuf>,
 }
 
 impl DeserializedResource for WorldgenSettings {
@@ -21,6 +30,8 @@ impl DeserializedResource for WorldgenSettings {
 #[derive(Debug, Copy, Clone, Deserialize)]
 pub struct IslandSettings {
     pub size: UVec2,
+    #[serde(default)]
+    pub mode: IslandMode,
     pub cutoff: f32,
     pub reshape_margin: f32,
     pub reshape_radius: f32,
@@ -28,6 +39,22 @@ pub struct IslandSettings {
     pub min_island_area: f32,
     pub min_total_area: f32,
     pub max_total_area: f32,
+    /// Drop every landmass except the largest one. Ignored in archipelago mode
+    #[serde(default = "default_keep_largest_landmass")]
+    pub keep_largest_landmass: bool,
+}
+
+fn default_keep_largest_landmass() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Deserialize)]
+pub enum IslandMode {
+    #[default]
+    Single,
+    Archipelago {
+        islands: u32,
+    },
 }
 
 #[derive(Debug, Copy, Clone, Deserialize)]
@@ -36,7 +63,12 @@ pub struct HeightSettings {
     pub land_height: f32,
     pub peak_height: f32,
     pub ocean_depth: f32,
-    pub warp_dist: f32,
+    /// Extra depth reached `ocean_floor_distance` cells away from the coast
+    pub ocean_floor_depth: f32,
+    pub ocean_floor_distance: f32,
+    /// Maximum displacement of the coastline by `height_warp`, in cells
+    #[serde(alias = "warp_dist")]
+    pub warp_strength: f32,
     pub mountain_power: f32,
 }
 
@@ -46,6 +78,44 @@ pub struct RiversSettings {
     pub inertia: f32,
     pub evaporation: f32,
     pub erosion: f32,
+    #[serde(default = "default_erosion_exponent")]
+    pub erosion_exponent: f32,
+    /// Multiplier applied to the accumulated erosion before the curve
+    #[serde(default = "default_erosion_strength")]
+    pub erosion_strength: f32,
+}
+
+fn default_erosion_exponent() -> f32 {
+    1.1
+}
+
+fn default_erosion_strength() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct LakesSettings {
+    pub min_depth: f32,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct CavesSettings {
+    /// Only cells higher than this can be carved
+    pub min_height: f32,
+    pub fill_probability: f32,
+    pub steps: u32,
+    pub min_area: usize,
+}
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub struct TemperatureSettings {
+    pub equator_temperature: f32,
+    pub pole_temperature: f32,
+    /// Temperature drop per unit of height above sea level
+    pub lapse_rate: f32,
+    pub noise_strength: f32,
+    pub tundra_below: f32,
+    pub desert_above: f32,
 }
 
 #[derive(Debug, Copy, Clone, Deserialize)]
@@ -60,5 +130,6 @@ pub struct NoiseSettings {
     pub height: FbmNoiseSettings,
     pub height_warp: FbmNoiseSettings,
     pub biomes: FbmNoiseSettings,
+    pub temperature: FbmNoiseSettings,
     pub grass: FbmNoiseSettings,
 }